@@ -0,0 +1,54 @@
+//! Compares the cost of advancing the global epoch of EBR with many registered participants: the
+//! list of participants and the array of epoch slots.
+//!
+//! Run with `cargo bench --bench epoch_advance`.
+
+use std::sync::Barrier;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use cdrc_rs::ebr_impl::{Collector, Config, Registry};
+
+/// The number of synchronizations, each of which advances the global epoch twice.
+const SYNCS: usize = 5000;
+
+/// Returns the average duration of an advancement of the global epoch.
+fn bench(threads: usize, registry: Registry) -> Duration {
+    let config = Config::builder().registry(registry).build().unwrap();
+    let collector = Collector::with_config(config);
+    let ready = Barrier::new(threads + 1);
+    let done = Barrier::new(threads + 1);
+
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            let (collector, ready, done) = (&collector, &ready, &done);
+            scope.spawn(move || {
+                // The participant stays registered but unpinned, so every advancement scans it.
+                let _handle = collector.register();
+                ready.wait();
+                done.wait();
+            });
+        }
+        ready.wait();
+
+        let handle = collector.register();
+        let start = Instant::now();
+        for _ in 0..SYNCS {
+            handle.synchronize();
+        }
+        let elapsed = start.elapsed() / (SYNCS * 2) as u32;
+
+        drop(handle);
+        done.wait();
+        elapsed
+    })
+}
+
+fn main() {
+    println!("threads     list    array");
+    for threads in [8, 16, 32, 64, 128] {
+        let list = bench(threads, Registry::List);
+        let array = bench(threads, Registry::Array);
+        println!("{:>7}  {:>7.1?}  {:>7.1?}", threads, list, array);
+    }
+}
@@ -0,0 +1,93 @@
+//! Compares the hazard scans of HP reclamation with many threads, each holding many hazard
+//! pointers: the hash set of `Domain::collect_guarded_ptrs` and `HazardSet`.
+//!
+//! Run with `cargo bench --bench hazard_scan`.
+
+use std::hint::black_box;
+use std::sync::Barrier;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use cdrc_rs::hp_impl::{Domain, HazardPointer, HazardSet, Thread};
+
+/// The number of retired objects probed by a scan.
+const RETIRED: usize = 4096;
+/// One of `PROTECTED_RATIO` retired objects is protected.
+const PROTECTED_RATIO: usize = 64;
+const SCANS: usize = 50;
+
+fn address(i: usize) -> *mut u8 {
+    (i * 64 + 64) as *mut u8
+}
+
+/// Returns the average durations of a scan with the hash set and with `HazardSet`.
+fn bench(threads: usize, slots: usize) -> (Duration, Duration) {
+    let domain = Domain::new();
+    let ready = Barrier::new(threads + 1);
+    let done = Barrier::new(threads + 1);
+    let retired: Vec<_> = (0..RETIRED)
+        .map(|i| {
+            if i % PROTECTED_RATIO == 0 {
+                address(i / PROTECTED_RATIO * threads * slots)
+            } else {
+                address(threads * slots + i)
+            }
+        })
+        .collect();
+
+    thread::scope(|scope| {
+        for t in 0..threads {
+            let (domain, ready, done) = (&domain, &ready, &done);
+            scope.spawn(move || {
+                let thread = Thread::new(domain);
+                let _hps: Vec<_> = (0..slots)
+                    .map(|s| {
+                        let mut hp = HazardPointer::new(&thread);
+                        hp.protect_raw(address(t * slots + s));
+                        hp
+                    })
+                    .collect();
+                ready.wait();
+                done.wait();
+            });
+        }
+        ready.wait();
+
+        let reader = Thread::new(&domain);
+        let start = Instant::now();
+        for _ in 0..SCANS {
+            let hazards = domain.collect_guarded_ptrs(&reader);
+            black_box(retired.iter().filter(|ptr| hazards.contains(ptr)).count());
+        }
+        let hash_set = start.elapsed() / SCANS as u32;
+
+        let mut hazards = HazardSet::new();
+        let start = Instant::now();
+        for _ in 0..SCANS {
+            hazards.collect(&domain, &reader);
+            black_box(retired.iter().filter(|ptr| hazards.contains(**ptr)).count());
+        }
+        let hazard_set = start.elapsed() / SCANS as u32;
+
+        drop(reader);
+        done.wait();
+        (hash_set, hazard_set)
+    })
+}
+
+fn main() {
+    println!("threads  slots  hazards  hash set  HazardSet");
+    for threads in [64, 128] {
+        for slots in [1, 8, 128] {
+            let (hash_set, hazard_set) = bench(threads, slots);
+            println!(
+                "{:>7}  {:>5}  {:>7}  {:>8.1?}  {:>9.1?}",
+                threads,
+                slots,
+                threads * slots,
+                hash_set,
+                hazard_set
+            );
+        }
+    }
+}
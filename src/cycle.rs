@@ -0,0 +1,596 @@
+//! Detection of reference cycles among [`Rc`] objects.
+//!
+//! The links of [`AtomicRc`] are strong, so a cycle of them keeps its objects alive after the
+//! last outside reference is dropped. The [`CycleDetector`] finds such cycles by trial deletion,
+//! in the style of Bacon and Rajan: starting from candidate objects, it walks the graph of strong
+//! links through the [`Trace`] implementations of the objects, subtracts the links inside the
+//! graph from the reference counts, and considers every object without a remaining outside
+//! reference, or reachable from one, to be alive. The rest of the graph is garbage, and its
+//! strongly connected components which contain a cycle are reported.
+//!
+//! The detector is a debugging aid, and is only compiled with the `cycle-detector` feature. It
+//! assumes that the traced objects are not modified concurrently, e.g., after the threads of a
+//! test have finished.
+
+use core::fmt;
+use std::collections::{HashMap, HashSet};
+
+use atomic::Ordering;
+
+use crate::{
+    AtomicRc, CountValue, CountedObject, Cs, Pointer, Rc, Tagged, Weak, WeakCountedObject,
+};
+
+/// A type whose strong links to other objects can be visited by a [`CycleDetector`].
+///
+/// # Examples
+///
+/// ```
+/// use cdrc_rs::{AtomicRc, Cs, Trace, Tracer};
+///
+/// struct Node<C: Cs> {
+///     next: AtomicRc<Node<C>, C>,
+/// }
+///
+/// impl<C: Cs> Trace<C> for Node<C> {
+///     fn trace(&self, tracer: &mut Tracer<'_, C>) {
+///         tracer.visit_atomic(&self.next);
+///     }
+/// }
+/// ```
+pub trait Trace<C: Cs> {
+    /// Visits every [`AtomicRc`] and [`Rc`] held by this object, with [`Tracer::visit_atomic`]
+    /// and [`Tracer::visit`] respectively.
+    ///
+    /// A link which is not visited is considered to be an outside reference, so that the objects
+    /// reachable from it are never reported.
+    fn trace(&self, tracer: &mut Tracer<'_, C>);
+}
+
+/// The type-erased operations on a counted object whose managed object implements [`Trace`].
+struct Meta<C: Cs> {
+    type_name: &'static str,
+    trace: unsafe fn(usize, &mut Tracer<'_, C>),
+    counts: unsafe fn(usize) -> (CountValue, CountValue),
+}
+
+impl<C: Cs> Clone for Meta<C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<C: Cs> Copy for Meta<C> {}
+
+impl<C: Cs> Meta<C> {
+    fn of<T: Trace<C>, O: CountedObject<Target = T>>() -> Self {
+        Self {
+            type_name: core::any::type_name::<T>(),
+            trace: trace::<T, C, O>,
+            counts: counts::<O>,
+        }
+    }
+}
+
+unsafe fn trace<T: Trace<C>, C: Cs, O: CountedObject<Target = T>>(
+    address: usize,
+    tracer: &mut Tracer<'_, C>,
+) {
+    (*(address as *const O)).data().trace(tracer);
+}
+
+unsafe fn counts<O: CountedObject>(address: usize) -> (CountValue, CountValue) {
+    let obj = &*(address as *const O);
+    (obj.ref_count(), obj.weak_count())
+}
+
+/// A strong link taken out of a garbage object, which is released after the traversal.
+struct Taken {
+    address: usize,
+    release: unsafe fn(usize),
+}
+
+unsafe fn release<T, C: Cs, O: CountedObject<Target = T>>(address: usize) {
+    drop(Rc::<T, C, O>::from_raw(Tagged::new(address as *mut O)));
+}
+
+enum Mode<'g, C: Cs> {
+    /// Records the targets of the links.
+    Scan(Vec<(usize, Meta<C>)>),
+    /// Takes the atomic links to the garbage objects.
+    Collect {
+        garbage: &'g HashSet<usize>,
+        taken: Vec<Taken>,
+    },
+}
+
+/// A visitor of the strong links of an object. See [`Trace`].
+pub struct Tracer<'g, C: Cs> {
+    mode: Mode<'g, C>,
+}
+
+impl<'g, C: Cs> Tracer<'g, C> {
+    /// Visits the link of `link`.
+    pub fn visit_atomic<T: Trace<C>, O: CountedObject<Target = T>>(
+        &mut self,
+        link: &AtomicRc<T, C, O>,
+    ) {
+        let address = link.load(Ordering::Acquire).as_raw() as usize;
+        if address == 0 {
+            return;
+        }
+        match &mut self.mode {
+            Mode::Scan(targets) => targets.push((address, Meta::of::<T, O>())),
+            Mode::Collect { garbage, taken } => {
+                if garbage.contains(&address) {
+                    let cs = C::new();
+                    let rc = link.swap(Rc::null(), Ordering::AcqRel, &cs);
+                    taken.push(Taken {
+                        address: rc.into_raw().as_raw() as usize,
+                        release: release::<T, C, O>,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Visits the link of `rc`.
+    ///
+    /// Such a link can not be broken by [`CycleDetector::collect`], but every cycle contains an
+    /// atomic link, as it can be only closed by modifying one.
+    pub fn visit<T: Trace<C>, O: CountedObject<Target = T>>(&mut self, rc: &Rc<T, C, O>) {
+        let address = rc.as_ptr().as_raw() as usize;
+        if let Mode::Scan(targets) = &mut self.mode {
+            if address != 0 {
+                targets.push((address, Meta::of::<T, O>()));
+            }
+        }
+    }
+}
+
+impl<C: Cs> fmt::Debug for Tracer<'_, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tracer").finish_non_exhaustive()
+    }
+}
+
+/// An object in a reported cycle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CycleMember {
+    /// The address of the counted object.
+    pub address: usize,
+    /// The name of the type of the managed object.
+    pub type_name: &'static str,
+    /// The strong reference count, all of which come from the garbage objects.
+    pub strong: CountValue,
+    /// The weak reference count, including the references of the detector.
+    pub weak: CountValue,
+}
+
+/// A strongly connected component of garbage objects, which is reachable only from itself and
+/// from other garbage objects.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle {
+    /// The objects of the component.
+    pub members: Vec<CycleMember>,
+}
+
+impl fmt::Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cycle of {} objects:", self.members.len())?;
+        for member in &self.members {
+            write!(
+                f,
+                "\n    {} at {:#x} (strong: {}, weak: {})",
+                member.type_name, member.address, member.strong, member.weak
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// An object of the traversed graph.
+struct Vertex<C: Cs> {
+    address: usize,
+    meta: Meta<C>,
+    strong: CountValue,
+    weak: CountValue,
+    edges: Vec<usize>,
+}
+
+/// The graph being traversed, with the vertices whose links are not visited yet.
+struct Graph<C: Cs> {
+    vertices: Vec<Vertex<C>>,
+    indices: HashMap<usize, usize>,
+    stack: Vec<usize>,
+}
+
+impl<C: Cs> Graph<C> {
+    /// Returns the index of the object at `address`, adding it if it is not in the graph yet.
+    /// Returns `None` if the object has been disposed.
+    fn visit(&mut self, address: usize, meta: Meta<C>) -> Option<usize> {
+        if let Some(&index) = self.indices.get(&address) {
+            return Some(index);
+        }
+        // The object is kept alive by a weak reference of the detector or a strong link of a live
+        // object.
+        let (strong, weak) = unsafe { (meta.counts)(address) };
+        if strong == 0 {
+            return None;
+        }
+        let index = self.vertices.len();
+        self.indices.insert(address, index);
+        self.vertices.push(Vertex {
+            address,
+            meta,
+            strong,
+            weak,
+            edges: Vec::new(),
+        });
+        self.stack.push(index);
+        Some(index)
+    }
+}
+
+/// A candidate held by the detector with a weak reference.
+struct Candidate<C: Cs> {
+    address: usize,
+    meta: Meta<C>,
+    release: unsafe fn(usize),
+}
+
+unsafe fn release_weak<T, C: Cs, O: WeakCountedObject<Target = T>>(address: usize) {
+    drop(Weak::<T, C, O>::from_raw(Tagged::new(address as *mut O)));
+}
+
+/// A detector of the reference cycles reachable from a set of candidate objects.
+///
+/// The candidates are typically the objects of a data structure which may be leaked in cycles,
+/// e.g., the nodes of a doubly linked list. The detector holds weak references to them, so that
+/// they can be examined after all outside references are dropped.
+///
+/// # Examples
+///
+/// ```
+/// use cdrc_rs::{AtomicRc, Cs, CsEBR, CycleDetector, Rc, StrongPtr, Trace, Tracer};
+/// use std::sync::atomic::Ordering;
+///
+/// struct Node {
+///     next: AtomicRc<Node, CsEBR>,
+/// }
+///
+/// impl Trace<CsEBR> for Node {
+///     fn trace(&self, tracer: &mut Tracer<'_, CsEBR>) {
+///         tracer.visit_atomic(&self.next);
+///     }
+/// }
+///
+/// let mut detector = CycleDetector::new();
+/// let node = Rc::<_, CsEBR>::new(Node { next: AtomicRc::null() });
+/// {
+///     let cs = &CsEBR::new();
+///     unsafe { node.deref() }.next.store(node.clone(cs), Ordering::Release, cs);
+/// }
+/// detector.add(&node);
+/// drop(node);
+///
+/// assert_eq!(detector.detect().len(), 1);
+/// assert_eq!(detector.collect().len(), 1);
+/// ```
+pub struct CycleDetector<C: Cs> {
+    candidates: Vec<Candidate<C>>,
+}
+
+impl<C: Cs> Default for CycleDetector<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: Cs> CycleDetector<C> {
+    /// Creates a detector without candidates.
+    pub fn new() -> Self {
+        Self {
+            candidates: Vec::new(),
+        }
+    }
+
+    /// Adds the object of `rc` to the candidates, if it is not null.
+    pub fn add<T: Trace<C>, O: WeakCountedObject<Target = T>>(&mut self, rc: &Rc<T, C, O>) {
+        if !rc.is_null() {
+            let weak = Weak::from_strong(rc, &C::new());
+            self.push::<T, O>(weak);
+        }
+    }
+
+    /// Adds the object of `weak` to the candidates, if it is not null.
+    pub fn add_weak<T: Trace<C>, O: WeakCountedObject<Target = T>>(
+        &mut self,
+        weak: &Weak<T, C, O>,
+    ) {
+        if !weak.is_null() {
+            let weak = weak.clone(&C::new());
+            self.push::<T, O>(weak);
+        }
+    }
+
+    fn push<T: Trace<C>, O: WeakCountedObject<Target = T>>(&mut self, weak: Weak<T, C, O>) {
+        self.candidates.push(Candidate {
+            address: weak.into_raw().as_raw() as usize,
+            meta: Meta::of::<T, O>(),
+            release: release_weak::<T, C, O>,
+        });
+    }
+
+    /// Returns the number of candidates.
+    pub fn len(&self) -> usize {
+        self.candidates.len()
+    }
+
+    /// Returns `true` if there is no candidate.
+    pub fn is_empty(&self) -> bool {
+        self.candidates.is_empty()
+    }
+
+    /// Reclaims all garbage of `C` with [`barrier`](crate::barrier), so that the reference counts
+    /// are up to date, and returns the cycles of garbage objects reachable from the candidates.
+    ///
+    /// # Panics
+    ///
+    /// For EBR, panics if the current thread is in a critical section.
+    pub fn detect(&self) -> Vec<Cycle> {
+        crate::barrier::<C>();
+        let (vertices, garbage) = self.garbage();
+        cycles(&vertices, &garbage)
+            .into_iter()
+            .map(|component| cycle(&vertices, component))
+            .collect()
+    }
+
+    /// Detects the cycles as [`Self::detect`], and breaks them by taking the atomic links between
+    /// the garbage objects, so that the objects are reclaimed as usual.
+    ///
+    /// # Panics
+    ///
+    /// For EBR, panics if the current thread is in a critical section.
+    pub fn collect(self) -> Vec<Cycle> {
+        crate::barrier::<C>();
+        let (vertices, garbage) = self.garbage();
+        let found: Vec<_> = cycles(&vertices, &garbage)
+            .into_iter()
+            .map(|component| cycle(&vertices, component))
+            .collect();
+
+        let addresses = garbage
+            .iter()
+            .map(|&index| vertices[index].address)
+            .collect();
+        let mut taken = Vec::new();
+        for &index in &garbage {
+            let vertex = &vertices[index];
+            let mut tracer = Tracer {
+                mode: Mode::Collect {
+                    garbage: &addresses,
+                    taken: Vec::new(),
+                },
+            };
+            // The object is alive, as it is referenced by other garbage objects.
+            unsafe { (vertex.meta.trace)(vertex.address, &mut tracer) };
+            if let Mode::Collect { taken: links, .. } = tracer.mode {
+                taken.extend(links);
+            }
+        }
+        // Released after the traversal, as the releases may destroy the garbage objects.
+        for link in taken {
+            unsafe { (link.release)(link.address) };
+        }
+        found
+    }
+
+    /// Traverses the graph reachable from the candidates, and returns it with the indices of its
+    /// garbage objects.
+    fn garbage(&self) -> (Vec<Vertex<C>>, Vec<usize>) {
+        let mut graph = Graph {
+            vertices: Vec::new(),
+            indices: HashMap::new(),
+            stack: Vec::new(),
+        };
+        for candidate in &self.candidates {
+            graph.visit(candidate.address, candidate.meta);
+        }
+        while let Some(index) = graph.stack.pop() {
+            let Vertex { address, meta, .. } = graph.vertices[index];
+            let mut tracer = Tracer {
+                mode: Mode::Scan(Vec::new()),
+            };
+            // The object has a positive strong reference count.
+            unsafe { (meta.trace)(address, &mut tracer) };
+            let Mode::Scan(targets) = tracer.mode else {
+                unreachable!()
+            };
+            for (target, meta) in targets {
+                if let Some(target) = graph.visit(target, meta) {
+                    graph.vertices[index].edges.push(target);
+                }
+            }
+        }
+        let vertices = graph.vertices;
+
+        // Trial deletion: subtract the links inside the graph from the reference counts.
+        let mut internal = vec![0 as CountValue; vertices.len()];
+        for vertex in &vertices {
+            for &target in &vertex.edges {
+                internal[target] += 1;
+            }
+        }
+        let mut live = vec![false; vertices.len()];
+        let mut stack: Vec<_> = (0..vertices.len())
+            .filter(|&index| vertices[index].strong > internal[index])
+            .collect();
+        while let Some(index) = stack.pop() {
+            if !live[index] {
+                live[index] = true;
+                stack.extend(&vertices[index].edges);
+            }
+        }
+
+        let garbage = (0..vertices.len()).filter(|&index| !live[index]).collect();
+        (vertices, garbage)
+    }
+}
+
+impl<C: Cs> Drop for CycleDetector<C> {
+    fn drop(&mut self) {
+        for candidate in self.candidates.drain(..) {
+            unsafe { (candidate.release)(candidate.address) };
+        }
+    }
+}
+
+impl<C: Cs> fmt::Debug for CycleDetector<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CycleDetector")
+            .field("candidates", &self.candidates.len())
+            .finish()
+    }
+}
+
+fn cycle<C: Cs>(vertices: &[Vertex<C>], component: Vec<usize>) -> Cycle {
+    Cycle {
+        members: component
+            .into_iter()
+            .map(|index| {
+                let vertex = &vertices[index];
+                CycleMember {
+                    address: vertex.address,
+                    type_name: vertex.meta.type_name,
+                    strong: vertex.strong,
+                    weak: vertex.weak,
+                }
+            })
+            .collect(),
+    }
+}
+
+/// Returns the strongly connected components of the `subset` of the vertices which contain a
+/// cycle.
+fn cycles<C: Cs>(vertices: &[Vertex<C>], subset: &[usize]) -> Vec<Vec<usize>> {
+    let edges: Vec<&[usize]> = vertices.iter().map(|v| v.edges.as_slice()).collect();
+    components(&edges, subset)
+        .into_iter()
+        .filter(|component| match component.as_slice() {
+            [index] => edges[*index].contains(index),
+            _ => true,
+        })
+        .collect()
+}
+
+/// Returns the strongly connected components of the subgraph induced by `subset`, with Tarjan's
+/// algorithm. The traversal keeps its own stack, as the graph may be a very long chain.
+fn components(edges: &[&[usize]], subset: &[usize]) -> Vec<Vec<usize>> {
+    const UNVISITED: usize = usize::MAX;
+
+    let mut included = vec![false; edges.len()];
+    for &index in subset {
+        included[index] = true;
+    }
+    let mut order = vec![UNVISITED; edges.len()];
+    let mut low = vec![0; edges.len()];
+    let mut on_stack = vec![false; edges.len()];
+    let mut stack = Vec::new();
+    let mut result = Vec::new();
+    let mut next = 0;
+
+    for &root in subset {
+        if order[root] != UNVISITED {
+            continue;
+        }
+        // The vertices being visited, with the positions of their next edges.
+        let mut path = vec![(root, 0)];
+        order[root] = next;
+        low[root] = next;
+        next += 1;
+        stack.push(root);
+        on_stack[root] = true;
+
+        while let Some(&mut (index, ref mut edge)) = path.last_mut() {
+            if let Some(&target) = edges[index].get(*edge) {
+                *edge += 1;
+                if !included[target] {
+                    continue;
+                }
+                if order[target] == UNVISITED {
+                    order[target] = next;
+                    low[target] = next;
+                    next += 1;
+                    stack.push(target);
+                    on_stack[target] = true;
+                    path.push((target, 0));
+                } else if on_stack[target] {
+                    low[index] = low[index].min(order[target]);
+                }
+                continue;
+            }
+
+            path.pop();
+            if let Some(&(parent, _)) = path.last() {
+                low[parent] = low[parent].min(low[index]);
+            }
+            if low[index] == order[index] {
+                let mut component = Vec::new();
+                loop {
+                    let member = stack.pop().unwrap();
+                    on_stack[member] = false;
+                    component.push(member);
+                    if member == index {
+                        break;
+                    }
+                }
+                result.push(component);
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::components;
+
+    fn sorted(mut components: Vec<Vec<usize>>) -> Vec<Vec<usize>> {
+        for component in &mut components {
+            component.sort_unstable();
+        }
+        components.sort_unstable();
+        components
+    }
+
+    #[test]
+    fn strongly_connected_components() {
+        // 0 -> 1 -> 2 -> 0, 2 -> 3 -> 4 -> 3, 5 -> 5, 6
+        let edges: [&[usize]; 7] = [&[1], &[2], &[0, 3], &[4], &[3], &[5], &[]];
+        let all: Vec<_> = (0..edges.len()).collect();
+        assert_eq!(
+            sorted(components(&edges, &all)),
+            vec![vec![0, 1, 2], vec![3, 4], vec![5], vec![6]]
+        );
+
+        // Excluding 1 breaks the first cycle.
+        assert_eq!(
+            sorted(components(&edges, &[0, 2, 3, 4])),
+            vec![vec![0], vec![2], vec![3, 4]]
+        );
+    }
+
+    #[test]
+    fn long_chain() {
+        const LENGTH: usize = 1_000_000;
+
+        let next: Vec<[usize; 1]> = (0..LENGTH).map(|i| [(i + 1) % LENGTH]).collect();
+        let edges: Vec<&[usize]> = next.iter().map(|e| e.as_slice()).collect();
+        let all: Vec<_> = (0..LENGTH).collect();
+        let found = components(&edges, &all);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].len(), LENGTH);
+    }
+}
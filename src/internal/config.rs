@@ -0,0 +1,347 @@
+//! Errors and parameters shared by the configurations of reclamation backends.
+
+use core::fmt;
+use core::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, AtomicUsize, Ordering};
+use core::time::Duration;
+use std::time::Instant;
+
+/// An error returned when a configuration has an invalid value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigError {
+    /// The named parameter must be positive.
+    Zero(&'static str),
+    /// The first named parameter must not be greater than the second one.
+    GreaterThan(&'static str, &'static str),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Zero(name) => write!(f, "`{}` must be positive", name),
+            ConfigError::GreaterThan(lhs, rhs) => {
+                write!(f, "`{}` must not be greater than `{}`", lhs, rhs)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Returns an error if `value` is zero.
+#[inline]
+pub(crate) fn check_positive(name: &'static str, value: usize) -> Result<(), ConfigError> {
+    if value == 0 {
+        Err(ConfigError::Zero(name))
+    } else {
+        Ok(())
+    }
+}
+
+/// What a retiring thread does when there are too many unreclaimed objects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backpressure {
+    /// Run the reclamation synchronously until it makes no more progress.
+    Collect,
+    /// Run the reclamation, and retry it until the low-water mark is reached or the given time
+    /// has passed.
+    ///
+    /// Objects retired by the thread itself may be protected by the thread, so the wait must be
+    /// bounded.
+    Block(Duration),
+}
+
+impl Backpressure {
+    /// How long to retry the reclamation.
+    pub(crate) fn max_wait(self) -> Duration {
+        match self {
+            Backpressure::Collect => Duration::ZERO,
+            Backpressure::Block(max_wait) => max_wait,
+        }
+    }
+}
+
+/// A limit on the number of unreclaimed objects of a collector or a domain.
+///
+/// Once the number of unreclaimed objects exceeds the high-water mark, threads retiring objects
+/// apply the backpressure until the number falls to the low-water mark.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GarbageLimit {
+    high_water: usize,
+    low_water: usize,
+    backpressure: Backpressure,
+}
+
+impl GarbageLimit {
+    /// Creates a limit. `low_water` must not be greater than `high_water`.
+    pub fn new(
+        high_water: usize,
+        low_water: usize,
+        backpressure: Backpressure,
+    ) -> Result<Self, ConfigError> {
+        check_positive("high_water", high_water)?;
+        if low_water > high_water {
+            return Err(ConfigError::GreaterThan("low_water", "high_water"));
+        }
+        Ok(Self {
+            high_water,
+            low_water,
+            backpressure,
+        })
+    }
+
+    /// The number of unreclaimed objects above which the backpressure applies.
+    pub fn high_water(&self) -> usize {
+        self.high_water
+    }
+
+    /// The number of unreclaimed objects at which the backpressure stops.
+    pub fn low_water(&self) -> usize {
+        self.low_water
+    }
+
+    /// What a retiring thread does above the high-water mark.
+    pub fn backpressure(&self) -> Backpressure {
+        self.backpressure
+    }
+}
+
+/// A `GarbageLimit` which can be read and replaced concurrently.
+///
+/// As with the other parameters, a reader racing with `store` may observe a mix of the old and the
+/// new limits. The backpressure tolerates it, as it stops at the latest when the wait is over.
+#[derive(Debug)]
+pub(crate) struct AtomicGarbageLimit {
+    /// `usize::MAX` if there is no limit.
+    high_water: AtomicUsize,
+    low_water: AtomicUsize,
+    /// In nanoseconds.
+    max_wait: AtomicU64,
+    block: AtomicBool,
+}
+
+impl AtomicGarbageLimit {
+    pub(crate) const fn new(limit: Option<GarbageLimit>) -> Self {
+        let (high_water, low_water, max_wait, block) = Self::encode(limit);
+        Self {
+            high_water: AtomicUsize::new(high_water),
+            low_water: AtomicUsize::new(low_water),
+            max_wait: AtomicU64::new(max_wait),
+            block: AtomicBool::new(block),
+        }
+    }
+
+    const fn encode(limit: Option<GarbageLimit>) -> (usize, usize, u64, bool) {
+        match limit {
+            None => (usize::MAX, usize::MAX, 0, false),
+            Some(GarbageLimit {
+                high_water,
+                low_water,
+                backpressure,
+            }) => match backpressure {
+                Backpressure::Collect => (high_water, low_water, 0, false),
+                Backpressure::Block(max_wait) => (high_water, low_water, nanos(max_wait), true),
+            },
+        }
+    }
+
+    pub(crate) fn load(&self) -> Option<GarbageLimit> {
+        let high_water = self.high_water();
+        if high_water == usize::MAX {
+            return None;
+        }
+        let backpressure = if self.block.load(Ordering::Relaxed) {
+            Backpressure::Block(Duration::from_nanos(self.max_wait.load(Ordering::Relaxed)))
+        } else {
+            Backpressure::Collect
+        };
+        Some(GarbageLimit {
+            high_water,
+            low_water: self.low_water.load(Ordering::Relaxed).min(high_water),
+            backpressure,
+        })
+    }
+
+    pub(crate) fn store(&self, limit: Option<GarbageLimit>) {
+        let (high_water, low_water, max_wait, block) = Self::encode(limit);
+        self.high_water.store(high_water, Ordering::Relaxed);
+        self.low_water.store(low_water, Ordering::Relaxed);
+        self.max_wait.store(max_wait, Ordering::Relaxed);
+        self.block.store(block, Ordering::Relaxed);
+    }
+
+    /// The high-water mark, or `usize::MAX` if there is no limit.
+    #[inline]
+    pub(crate) fn high_water(&self) -> usize {
+        self.high_water.load(Ordering::Relaxed)
+    }
+}
+
+/// A bound on the destructor work of a single reclamation.
+///
+/// The objects left over by an exhausted budget stay in the collector or the domain, and are
+/// reclaimed by later reclamations of any thread.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReclaimBudget {
+    /// Reclaim at most the given number of objects.
+    Objects(usize),
+    /// Stop reclaiming once the given time has passed. The time is checked between objects, so a
+    /// slow destructor may overrun it.
+    Time(Duration),
+}
+
+impl ReclaimBudget {
+    pub(crate) fn check(budget: Option<Self>) -> Result<(), ConfigError> {
+        match budget {
+            Some(ReclaimBudget::Objects(0)) => Err(ConfigError::Zero("reclaim_budget")),
+            Some(ReclaimBudget::Time(time)) if time.is_zero() => {
+                Err(ConfigError::Zero("reclaim_budget"))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// An optional `ReclaimBudget` which can be read and replaced concurrently.
+#[derive(Debug)]
+pub(crate) struct AtomicReclaimBudget {
+    /// `NONE`, `OBJECTS` or `TIME`.
+    kind: AtomicU8,
+    /// The number of objects, or the time in nanoseconds.
+    value: AtomicU64,
+}
+
+impl AtomicReclaimBudget {
+    const NONE: u8 = 0;
+    const OBJECTS: u8 = 1;
+    const TIME: u8 = 2;
+
+    pub(crate) const fn new(budget: Option<ReclaimBudget>) -> Self {
+        let (kind, value) = Self::encode(budget);
+        Self {
+            kind: AtomicU8::new(kind),
+            value: AtomicU64::new(value),
+        }
+    }
+
+    const fn encode(budget: Option<ReclaimBudget>) -> (u8, u64) {
+        match budget {
+            None => (Self::NONE, 0),
+            Some(ReclaimBudget::Objects(objects)) => (Self::OBJECTS, objects as u64),
+            Some(ReclaimBudget::Time(time)) => (Self::TIME, nanos(time)),
+        }
+    }
+
+    pub(crate) fn load(&self) -> Option<ReclaimBudget> {
+        // A reader racing with `store` may pair the new kind with the old value, which may be
+        // zero. Round it up, so that a reclamation always makes progress.
+        let value = self.value.load(Ordering::Relaxed).max(1);
+        match self.kind.load(Ordering::Relaxed) {
+            Self::OBJECTS => Some(ReclaimBudget::Objects(
+                usize::try_from(value).unwrap_or(usize::MAX),
+            )),
+            Self::TIME => Some(ReclaimBudget::Time(Duration::from_nanos(value))),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn store(&self, budget: Option<ReclaimBudget>) {
+        let (kind, value) = Self::encode(budget);
+        self.value.store(value, Ordering::Relaxed);
+        self.kind.store(kind, Ordering::Relaxed);
+    }
+}
+
+/// The remaining budget of a reclamation in progress.
+#[derive(Debug)]
+pub(crate) struct Budget {
+    objects: usize,
+    deadline: Option<Instant>,
+}
+
+impl Budget {
+    pub(crate) fn new(budget: Option<ReclaimBudget>) -> Self {
+        match budget {
+            None => Self::unlimited(),
+            Some(ReclaimBudget::Objects(objects)) => Self {
+                objects,
+                deadline: None,
+            },
+            Some(ReclaimBudget::Time(time)) => Self {
+                objects: usize::MAX,
+                deadline: Instant::now().checked_add(time),
+            },
+        }
+    }
+
+    pub(crate) fn unlimited() -> Self {
+        Self {
+            objects: usize::MAX,
+            deadline: None,
+        }
+    }
+
+    /// Returns `true` if the budget is bounded at all.
+    pub(crate) fn is_limited(&self) -> bool {
+        self.objects != usize::MAX || self.deadline.is_some()
+    }
+
+    /// Returns `true` if no more object may be reclaimed.
+    #[inline]
+    pub(crate) fn is_exhausted(&self) -> bool {
+        self.objects == 0
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// Accounts for a reclaimed object.
+    #[inline]
+    pub(crate) fn spend(&mut self) {
+        self.objects = self.objects.saturating_sub(1);
+    }
+}
+
+/// Converts `duration` to nanoseconds, saturating at `u64::MAX`.
+pub(crate) const fn nanos(duration: Duration) -> u64 {
+    let nanos = duration.as_nanos();
+    if nanos > u64::MAX as u128 {
+        u64::MAX
+    } else {
+        nanos as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reclaim_budget() {
+        assert!(ReclaimBudget::check(Some(ReclaimBudget::Objects(0))).is_err());
+        assert!(ReclaimBudget::check(Some(ReclaimBudget::Time(Duration::ZERO))).is_err());
+
+        let atomic = AtomicReclaimBudget::new(None);
+        assert_eq!(atomic.load(), None);
+        for budget in [
+            Some(ReclaimBudget::Objects(3)),
+            Some(ReclaimBudget::Time(Duration::from_millis(5))),
+            None,
+        ] {
+            atomic.store(budget);
+            assert_eq!(atomic.load(), budget);
+        }
+
+        let mut budget = Budget::new(Some(ReclaimBudget::Objects(2)));
+        assert!(budget.is_limited());
+        budget.spend();
+        assert!(!budget.is_exhausted());
+        budget.spend();
+        assert!(budget.is_exhausted());
+
+        let budget = Budget::new(Some(ReclaimBudget::Time(Duration::from_nanos(1))));
+        std::thread::sleep(Duration::from_millis(1));
+        assert!(budget.is_exhausted());
+
+        assert!(!Budget::new(None).is_limited());
+    }
+}
@@ -0,0 +1,174 @@
+//! Histograms of the latency from retirement to reclamation.
+//!
+//! Latencies are counted in buckets of exponentially growing width, so that a histogram covers
+//! any latency with a fixed amount of memory and can be updated with a single atomic addition.
+
+use core::sync::atomic::{AtomicU64, Ordering};
+use core::time::Duration;
+
+/// The number of buckets of a histogram.
+const BUCKETS: usize = 64;
+
+/// Returns the index of the bucket of the latency of `nanos` nanoseconds.
+#[inline]
+fn bucket_of(nanos: u64) -> usize {
+    // `nanos | 1` maps zero to the first bucket along with one.
+    (u64::BITS - 1 - (nanos | 1).leading_zeros()) as usize
+}
+
+#[inline]
+fn nanos(latency: Duration) -> u64 {
+    u64::try_from(latency.as_nanos()).unwrap_or(u64::MAX)
+}
+
+/// A snapshot of a histogram of the latencies from retiring objects to ejecting them.
+///
+/// The `i`-th bucket counts the latencies in `[2^i, 2^(i+1))` nanoseconds, except that the first
+/// bucket also counts zero.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    buckets: [u64; BUCKETS],
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self {
+            buckets: [0; BUCKETS],
+        }
+    }
+}
+
+impl LatencyHistogram {
+    /// The number of buckets.
+    pub const BUCKETS: usize = BUCKETS;
+
+    /// Returns the counts of the buckets.
+    pub fn buckets(&self) -> &[u64; BUCKETS] {
+        &self.buckets
+    }
+
+    /// Returns the range of the latencies counted by the `index`-th bucket.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not less than [`Self::BUCKETS`].
+    pub fn bucket_range(index: usize) -> (Duration, Duration) {
+        assert!(index < BUCKETS, "bucket index out of range");
+        let lower = if index == 0 { 0 } else { 1 << index };
+        let upper = (1u64 << index).saturating_mul(2);
+        (Duration::from_nanos(lower), Duration::from_nanos(upper))
+    }
+
+    /// Returns the total number of recorded latencies.
+    pub fn count(&self) -> u64 {
+        self.buckets.iter().sum()
+    }
+
+    /// Returns an upper bound of the `q`-quantile of the recorded latencies, i.e., the upper end
+    /// of the bucket which contains it, or `None` if no latency is recorded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `q` is not in `[0, 1]`.
+    pub fn quantile(&self, q: f64) -> Option<Duration> {
+        assert!((0.0..=1.0).contains(&q), "quantile out of range");
+        let count = self.count();
+        if count == 0 {
+            return None;
+        }
+        let rank = ((q * count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (index, bucket) in self.buckets.iter().enumerate() {
+            seen += bucket;
+            if seen >= rank {
+                return Some(Self::bucket_range(index).1);
+            }
+        }
+        unreachable!()
+    }
+
+    /// Records `n` latencies of `latency`.
+    #[inline]
+    pub(crate) fn record(&mut self, latency: Duration, n: u64) {
+        self.buckets[bucket_of(nanos(latency))] += n;
+    }
+}
+
+/// A histogram which can be updated concurrently.
+#[derive(Debug)]
+pub(crate) struct AtomicHistogram {
+    buckets: [AtomicU64; BUCKETS],
+}
+
+impl AtomicHistogram {
+    pub(crate) const fn new() -> Self {
+        #[allow(clippy::declare_interior_mutable_const)]
+        const ZERO: AtomicU64 = AtomicU64::new(0);
+        Self {
+            buckets: [ZERO; BUCKETS],
+        }
+    }
+
+    /// Records `n` latencies of `latency`.
+    #[inline]
+    pub(crate) fn record(&self, latency: Duration, n: u64) {
+        self.buckets[bucket_of(nanos(latency))].fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Adds the counts of `histogram`.
+    pub(crate) fn merge(&self, histogram: &LatencyHistogram) {
+        for (bucket, &n) in self.buckets.iter().zip(&histogram.buckets) {
+            if n != 0 {
+                bucket.fetch_add(n, Ordering::Relaxed);
+            }
+        }
+    }
+
+    pub(crate) fn snapshot(&self) -> LatencyHistogram {
+        let mut histogram = LatencyHistogram::default();
+        for (n, bucket) in histogram.buckets.iter_mut().zip(&self.buckets) {
+            *n = bucket.load(Ordering::Relaxed);
+        }
+        histogram
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buckets() {
+        assert_eq!(bucket_of(0), 0);
+        assert_eq!(bucket_of(1), 0);
+        assert_eq!(bucket_of(2), 1);
+        assert_eq!(bucket_of(1023), 9);
+        assert_eq!(bucket_of(1024), 10);
+        assert_eq!(bucket_of(u64::MAX), BUCKETS - 1);
+
+        for index in 0..BUCKETS {
+            let (lower, upper) = LatencyHistogram::bucket_range(index);
+            assert_eq!(bucket_of(nanos(lower)), index);
+            if index < BUCKETS - 1 {
+                assert_eq!(bucket_of(nanos(upper)), index + 1);
+            }
+        }
+    }
+
+    #[test]
+    fn quantile() {
+        let histogram = AtomicHistogram::new();
+        assert_eq!(histogram.snapshot().quantile(0.5), None);
+
+        histogram.record(Duration::from_nanos(100), 9);
+        let mut batch = LatencyHistogram::default();
+        batch.record(Duration::from_micros(100), 1);
+        histogram.merge(&batch);
+
+        let snapshot = histogram.snapshot();
+        assert_eq!(snapshot.count(), 10);
+        assert_eq!(snapshot.quantile(0.0), Some(Duration::from_nanos(128)));
+        assert_eq!(snapshot.quantile(0.9), Some(Duration::from_nanos(128)));
+        assert_eq!(snapshot.quantile(1.0), Some(Duration::from_nanos(131_072)));
+    }
+}
@@ -0,0 +1,168 @@
+//! Tracking of live counted objects, to detect leaks in tests.
+//!
+//! With the `leak-check` feature, every object allocated by `Cs::create_object` is registered
+//! until `Cs::own_object` takes it back. Without it, `track` and `untrack` compile to nothing.
+
+use crate::internal::utils::CountedObject;
+use crate::internal::Cs;
+
+#[cfg(feature = "leak-check")]
+mod imp {
+    use core::any::type_name;
+    use core::fmt;
+    use std::collections::BTreeMap;
+    use std::sync::{Mutex, MutexGuard, PoisonError};
+
+    use crate::internal::utils::{CountValue, CountedObject};
+    use crate::internal::Cs;
+
+    struct Entry {
+        backend: &'static str,
+        type_name: &'static str,
+        counts: unsafe fn(*const u8) -> (CountValue, CountValue),
+    }
+
+    /// The live objects by their addresses.
+    static LIVE: Mutex<BTreeMap<usize, Entry>> = Mutex::new(BTreeMap::new());
+
+    fn live() -> MutexGuard<'static, BTreeMap<usize, Entry>> {
+        LIVE.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    unsafe fn counts<O: CountedObject>(ptr: *const u8) -> (CountValue, CountValue) {
+        let obj = &*ptr.cast::<O>();
+        (obj.ref_count(), obj.weak_count())
+    }
+
+    pub(crate) fn track<C: Cs, O: CountedObject>(ptr: *mut O) {
+        let entry = Entry {
+            backend: type_name::<C>(),
+            type_name: type_name::<O::Target>(),
+            counts: counts::<O>,
+        };
+        live().insert(ptr as usize, entry);
+    }
+
+    pub(crate) fn untrack<O>(ptr: *mut O) {
+        live().remove(&(ptr as usize));
+    }
+
+    /// A counted object which has been allocated but not destroyed yet.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct LiveObject {
+        /// The address of the counted object.
+        pub address: usize,
+        /// The name of the type of the managed object.
+        pub type_name: &'static str,
+        /// The name of the `Cs` which allocated the object.
+        pub backend: &'static str,
+        /// The strong reference count.
+        pub strong: CountValue,
+        /// The weak reference count.
+        pub weak: CountValue,
+    }
+
+    impl fmt::Display for LiveObject {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(
+                f,
+                "{} at {:#x} (strong: {}, weak: {})",
+                self.type_name, self.address, self.strong, self.weak
+            )
+        }
+    }
+
+    /// Returns the objects which have been allocated but not destroyed yet.
+    ///
+    /// The reference counts are read while the objects can not be destroyed, but they may change
+    /// concurrently.
+    pub fn live_objects() -> Vec<LiveObject> {
+        live()
+            .iter()
+            .map(|(&address, entry)| {
+                // The object is not destroyed before it is removed from `LIVE`.
+                let (strong, weak) = unsafe { (entry.counts)(address as *const u8) };
+                LiveObject {
+                    address,
+                    type_name: entry.type_name,
+                    backend: entry.backend,
+                    strong,
+                    weak,
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the numbers of live objects by the names of their types.
+    pub fn live_counts() -> BTreeMap<&'static str, usize> {
+        let mut counts = BTreeMap::new();
+        for entry in live().values() {
+            *counts.entry(entry.type_name).or_default() += 1;
+        }
+        counts
+    }
+
+    /// Reclaims all garbage of `C` with [`barrier`](crate::barrier), and then panics if any object
+    /// allocated by `C` is still alive.
+    ///
+    /// Objects which are still reachable, e.g., from a data structure of a concurrent test, are
+    /// reported as well. Use [`assert_no_leaks_of`] to check the objects of a type.
+    pub fn assert_no_leaks<C: Cs>() {
+        assert_no_leaks_matching::<C>(|_| true);
+    }
+
+    /// Reclaims all garbage of `C` with [`barrier`](crate::barrier), and then panics if any object
+    /// managing a `T` allocated by `C` is still alive.
+    pub fn assert_no_leaks_of<C: Cs, T>() {
+        let name = type_name::<T>();
+        assert_no_leaks_matching::<C>(|object| object.type_name == name);
+    }
+
+    fn assert_no_leaks_matching<C: Cs>(filter: impl Fn(&LiveObject) -> bool) {
+        const REPORTED: usize = 16;
+
+        crate::barrier::<C>();
+        let backend = type_name::<C>();
+        let leaked: Vec<_> = live_objects()
+            .into_iter()
+            .filter(|object| object.backend == backend && filter(object))
+            .collect();
+        if leaked.is_empty() {
+            return;
+        }
+
+        let mut report = format!("{} objects leaked:", leaked.len());
+        for object in leaked.iter().take(REPORTED) {
+            report.push_str(&format!("\n    {}", object));
+        }
+        if leaked.len() > REPORTED {
+            report.push_str("\n    ...");
+        }
+        panic!("{}", report);
+    }
+}
+
+#[cfg(feature = "leak-check")]
+pub use imp::{assert_no_leaks, assert_no_leaks_of, live_counts, live_objects, LiveObject};
+
+/// Registers an object allocated by `C`.
+#[inline(always)]
+#[cfg_attr(
+    not(feature = "leak-check"),
+    allow(clippy::extra_unused_type_parameters)
+)]
+pub(crate) fn track<C: Cs, O: CountedObject>(ptr: *mut O) {
+    #[cfg(feature = "leak-check")]
+    imp::track::<C, O>(ptr);
+    #[cfg(not(feature = "leak-check"))]
+    let _ = ptr;
+}
+
+/// Unregisters an object which is about to be destroyed.
+#[inline(always)]
+pub(crate) fn untrack<O>(ptr: *mut O) {
+    #[cfg(feature = "leak-check")]
+    imp::untrack(ptr);
+    #[cfg(not(feature = "leak-check"))]
+    let _ = ptr;
+}
@@ -1,9 +1,28 @@
+mod config;
+pub(crate) mod histogram;
+pub(crate) mod leak;
+pub(crate) mod observer;
+pub(crate) mod pool;
+mod reclaimer;
 mod smr;
 mod smr_common;
+pub(crate) mod stats;
+pub(crate) mod unwind;
 mod utils;
 
+pub use config::{Backpressure, ConfigError, GarbageLimit, ReclaimBudget};
+pub use histogram::LatencyHistogram;
+#[cfg(feature = "leak-check")]
+pub use leak::{assert_no_leaks, assert_no_leaks_of, live_counts, live_objects, LiveObject};
+#[cfg(feature = "observer")]
+pub use observer::{clear_observer, set_observer, Observer};
+pub use reclaimer::{Reclaimable, Reclaimer};
 pub use smr::{ebr_impl, hp_impl, CsEBR, CsHP};
 pub use smr_common::{Acquired, Cs, RetireType};
-pub use utils::{Counted, EjectAction, Pointer, TaggedCnt};
-
-pub(crate) use utils::*;
+pub use stats::Stats;
+pub use unwind::PanicPolicy;
+pub(crate) use utils::AssertSend;
+pub use utils::{
+    Count, CountValue, Counted, CountedNoWeak, CountedObject, EjectAction, IntrusiveCounted,
+    Pointer, Tagged, TaggedCnt, WeakCountedObject,
+};
@@ -0,0 +1,75 @@
+//! Hooks for observing reclamation events.
+//!
+//! The hooks are available with the `observer` feature. Without it, `notify!` expands to nothing,
+//! so that the reclamation paths carry no trace of the hooks.
+
+#[cfg(feature = "observer")]
+mod imp {
+    use core::ptr;
+    use core::sync::atomic::{AtomicPtr, Ordering};
+
+    use crate::internal::ebr_impl::Epoch;
+    use crate::internal::RetireType;
+
+    /// A receiver of reclamation events.
+    ///
+    /// The hooks are called synchronously by the thread which causes the event, in the middle of
+    /// reclamation. Hence they must be cheap, and must not retire, dispose or destroy objects
+    /// themselves. Objects are identified by the addresses of their counted objects, which may be
+    /// reused after the objects are destroyed.
+    pub trait Observer: Sync {
+        /// Called when an object is retired with `ret_type`.
+        fn on_retire(&self, _object: *const u8, _ret_type: RetireType) {}
+
+        /// Called when the managed value of an object has been dropped.
+        fn on_dispose(&self, _object: *const u8) {}
+
+        /// Called when the memory of an object is about to be released.
+        fn on_destroy(&self, _object: *const u8) {}
+
+        /// Called when a thread has advanced the global epoch of an EBR collector to `epoch`.
+        fn on_epoch_advance(&self, _epoch: Epoch) {}
+
+        /// Called when a thread has completed a hazard scan of an HP domain, which reclaimed
+        /// `reclaimed` retired objects and left `remaining` of them protected.
+        fn on_reclamation(&self, _reclaimed: usize, _remaining: usize) {}
+    }
+
+    /// The registered observer, boxed to make a thin pointer out of the trait object.
+    static OBSERVER: AtomicPtr<&'static dyn Observer> = AtomicPtr::new(ptr::null_mut());
+
+    #[inline]
+    pub(crate) fn get() -> Option<&'static dyn Observer> {
+        unsafe { OBSERVER.load(Ordering::Acquire).as_ref() }.copied()
+    }
+
+    /// Registers `observer`, replacing the previous one.
+    pub fn set_observer(observer: &'static dyn Observer) {
+        // The box is leaked, as a concurrent thread may still be reading the previous one.
+        let observer = Box::into_raw(Box::new(observer));
+        OBSERVER.store(observer, Ordering::Release);
+    }
+
+    /// Unregisters the observer.
+    pub fn clear_observer() {
+        OBSERVER.store(ptr::null_mut(), Ordering::Release);
+    }
+}
+
+#[cfg(feature = "observer")]
+pub(crate) use imp::get;
+#[cfg(feature = "observer")]
+pub use imp::{clear_observer, set_observer, Observer};
+
+/// Calls a hook of the registered observer, if any. Expands to nothing without the `observer`
+/// feature.
+macro_rules! notify {
+    ($hook:ident($($arg:expr),* $(,)?)) => {
+        #[cfg(feature = "observer")]
+        if let Some(observer) = $crate::internal::observer::get() {
+            observer.$hook($($arg),*);
+        }
+    };
+}
+
+pub(crate) use notify;
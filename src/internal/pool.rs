@@ -0,0 +1,282 @@
+//! A recycling pool for the memory blocks of `Counted<T>` objects.
+//!
+//! Each thread keeps a free list of blocks per memory layout, so that `create_object` can reuse a
+//! block released by `destroy` instead of going through the global allocator. When a thread-local
+//! list grows beyond `LOCAL_CAPACITY`, a batch of its blocks is moved to a global overflow list,
+//! from which threads with an empty local list refill. This way, memory released by a reclaiming
+//! thread can migrate back to the threads that allocate.
+//!
+//! Blocks are obtained from and returned to the global allocator with the layout of `T`, which is
+//! exactly what `Box<T>` uses. Hence a block allocated by a `Box` may be recycled into the pool
+//! and vice versa, and the pool can be enabled or disabled at any time.
+//!
+//! The pool itself knows nothing about SMR: it is the caller's responsibility to release a block
+//! only when no thread can access it anymore. In particular, the HP backend recycles a block only
+//! after a hazard scan has confirmed that it is not protected.
+
+use core::mem;
+use core::ptr::{self, NonNull};
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::alloc::{self, Layout};
+use std::cell::RefCell;
+use std::sync::Mutex;
+
+/// The maximum number of blocks per layout that a thread-local free list holds.
+const LOCAL_CAPACITY: usize = 64;
+
+/// The number of blocks moved between a thread-local and the global free list at once.
+const TRANSFER_BATCH: usize = LOCAL_CAPACITY / 2;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// The global overflow list.
+static GLOBAL: Mutex<Vec<FreeList>> = Mutex::new(Vec::new());
+
+/// The number of blocks of any layout in the global overflow list.
+///
+/// It is only updated while `GLOBAL` is locked, and lets a thread with an empty local list skip
+/// the lock when there is nothing to refill from.
+static GLOBAL_LEN: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static LOCAL: LocalPool = const { LocalPool { lists: RefCell::new(Vec::new()) } };
+}
+
+/// Enables or disables recycling of `Counted<T>` blocks.
+#[inline]
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+#[inline]
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Releases the blocks in the global overflow list and the current thread's free lists to the
+/// global allocator.
+pub fn purge() {
+    let _ = LOCAL.try_with(|local| {
+        for list in local.lists.take() {
+            list.release();
+        }
+    });
+    let lists = {
+        let mut global = GLOBAL.lock().unwrap();
+        GLOBAL_LEN.store(0, Ordering::Relaxed);
+        mem::take(&mut *global)
+    };
+    for list in lists {
+        list.release();
+    }
+}
+
+/// Free blocks of a single layout.
+struct FreeList {
+    layout: Layout,
+    blocks: Vec<NonNull<u8>>,
+}
+
+/// Blocks in a `FreeList` are unused memory, so it is safe to hand them over to another thread.
+unsafe impl Send for FreeList {}
+
+impl FreeList {
+    fn release(self) {
+        for block in self.blocks {
+            unsafe { alloc::dealloc(block.as_ptr(), self.layout) };
+        }
+    }
+}
+
+fn find_list(lists: &mut Vec<FreeList>, layout: Layout) -> &mut FreeList {
+    let idx = match lists.iter().position(|list| list.layout == layout) {
+        Some(idx) => idx,
+        None => {
+            lists.push(FreeList {
+                layout,
+                blocks: Vec::new(),
+            });
+            lists.len() - 1
+        }
+    };
+    &mut lists[idx]
+}
+
+struct LocalPool {
+    lists: RefCell<Vec<FreeList>>,
+}
+
+impl LocalPool {
+    fn pop(&self, layout: Layout) -> Option<NonNull<u8>> {
+        let mut lists = self.lists.borrow_mut();
+        let list = find_list(&mut lists, layout);
+        if list.blocks.is_empty() && GLOBAL_LEN.load(Ordering::Relaxed) != 0 {
+            let mut global = GLOBAL.lock().unwrap();
+            let global = find_list(&mut global, layout);
+            let from = global.blocks.len().saturating_sub(TRANSFER_BATCH);
+            GLOBAL_LEN.fetch_sub(global.blocks.len() - from, Ordering::Relaxed);
+            list.blocks.extend(global.blocks.drain(from..));
+        }
+        list.blocks.pop()
+    }
+
+    fn push(&self, block: NonNull<u8>, layout: Layout) {
+        let mut lists = self.lists.borrow_mut();
+        let list = find_list(&mut lists, layout);
+        if list.blocks.len() >= LOCAL_CAPACITY {
+            let mut global = GLOBAL.lock().unwrap();
+            let from = list.blocks.len() - TRANSFER_BATCH;
+            GLOBAL_LEN.fetch_add(TRANSFER_BATCH, Ordering::Relaxed);
+            find_list(&mut global, layout)
+                .blocks
+                .extend(list.blocks.drain(from..));
+        }
+        list.blocks.push(block);
+    }
+}
+
+impl Drop for LocalPool {
+    fn drop(&mut self) {
+        let mut global = GLOBAL.lock().unwrap();
+        for list in self.lists.get_mut().drain(..) {
+            GLOBAL_LEN.fetch_add(list.blocks.len(), Ordering::Relaxed);
+            find_list(&mut global, list.layout)
+                .blocks
+                .extend(list.blocks);
+        }
+    }
+}
+
+/// Moves `value` into a block drawn from the pool, or into a new `Box` if the pool is disabled.
+#[inline]
+pub(crate) fn alloc<T>(value: T) -> *mut T {
+    if !is_enabled() {
+        return Box::into_raw(Box::new(value));
+    }
+
+    let layout = Layout::new::<T>();
+    let block = LOCAL
+        .try_with(|local| local.pop(layout))
+        .ok()
+        .flatten()
+        .map(|block| block.as_ptr())
+        .unwrap_or_else(|| {
+            let block = unsafe { alloc::alloc(layout) };
+            if block.is_null() {
+                alloc::handle_alloc_error(layout);
+            }
+            block
+        });
+
+    let ptr = block.cast::<T>();
+    unsafe { ptr::write(ptr, value) };
+    ptr
+}
+
+/// Moves the value out of `ptr` and recycles its block.
+///
+/// # Safety
+///
+/// `ptr` must have been returned by `alloc` (or `Box::into_raw`), and no thread may access the
+/// block after this call.
+#[inline]
+pub(crate) unsafe fn take<T>(ptr: *mut T) -> T {
+    let value = ptr::read(ptr);
+    free(ptr);
+    value
+}
+
+/// Recycles the block of `ptr`, whose value must have already been moved out or dropped.
+///
+/// If the pool is disabled, the block is returned to the global allocator instead.
+///
+/// # Safety
+///
+/// `ptr` must have been returned by `alloc` (or `Box::into_raw`), and no thread may access the
+/// block after this call.
+#[inline]
+pub(crate) unsafe fn free<T>(ptr: *mut T) {
+    let layout = Layout::new::<T>();
+    let block = NonNull::new_unchecked(ptr.cast::<u8>());
+    if !is_enabled() {
+        dealloc(ptr);
+        return;
+    }
+
+    if LOCAL.try_with(|local| local.push(block, layout)).is_err() {
+        // The thread-local pool is already destroyed. Give the block to the other threads.
+        let mut global = GLOBAL.lock().unwrap();
+        GLOBAL_LEN.fetch_add(1, Ordering::Relaxed);
+        find_list(&mut global, layout).blocks.push(block);
+    }
+}
+
+/// Returns the block of `ptr`, whose value must have already been moved out or dropped, to the
+/// global allocator.
+///
+/// # Safety
+///
+/// `ptr` must have been returned by `alloc` (or `Box::into_raw`), and no thread may access the
+/// block after this call.
+#[inline]
+pub(crate) unsafe fn dealloc<T>(ptr: *mut T) {
+    alloc::dealloc(ptr.cast::<u8>(), Layout::new::<T>());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn recycle_locally() {
+        let pool = LocalPool {
+            lists: RefCell::new(Vec::new()),
+        };
+        let layout = Layout::new::<[u8; 24]>();
+        let block = NonNull::new(unsafe { alloc::alloc(layout) }).unwrap();
+
+        pool.push(block, layout);
+        assert_eq!(pool.pop(Layout::new::<[u16; 12]>()), None);
+        assert_eq!(pool.pop(layout), Some(block));
+        unsafe { alloc::dealloc(block.as_ptr(), layout) };
+    }
+
+    #[test]
+    fn overflow_migrates_between_threads() {
+        // A layout which is not used by other tests.
+        let layout = Layout::from_size_align(136, 8).unwrap();
+        let blocks = thread::spawn(move || {
+            let pool = LocalPool {
+                lists: RefCell::new(Vec::new()),
+            };
+            (0..LOCAL_CAPACITY + 1)
+                .map(|_| {
+                    let block = NonNull::new(unsafe { alloc::alloc(layout) }).unwrap();
+                    pool.push(block, layout);
+                    block.as_ptr() as usize
+                })
+                .collect::<Vec<_>>()
+        })
+        .join()
+        .unwrap();
+
+        // The overflowed batch and the rest of the exited thread's list are now global.
+        let pool = LocalPool {
+            lists: RefCell::new(Vec::new()),
+        };
+        let mut reused = Vec::new();
+        while let Some(block) = pool.pop(layout) {
+            reused.push(block.as_ptr() as usize);
+        }
+        reused.sort_unstable();
+        let mut blocks = blocks;
+        blocks.sort_unstable();
+        assert_eq!(reused, blocks);
+
+        for block in reused {
+            unsafe { alloc::dealloc(block as *mut u8, layout) };
+        }
+    }
+}
@@ -0,0 +1,182 @@
+//! Background reclamation.
+//!
+//! A `Reclaimer` runs a dedicated thread which periodically reclaims the garbage of an EBR
+//! collector or an HP domain. While a reclaimer is running, the other threads only hand their
+//! garbage over to the collector or the domain, and never run destructors in the middle of
+//! `defer`, except for the backpressure of a garbage limit and explicit flushes.
+
+use core::fmt;
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::time::Duration;
+use std::io;
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use crate::internal::ebr_impl::Collector;
+use crate::internal::hp_impl::{Domain, Thread};
+
+pub(crate) mod sealed {
+    use super::*;
+
+    /// Operations of a background reclaimer on its target.
+    ///
+    /// This trait is not exported, so that only this crate can implement [`Reclaimable`].
+    pub trait ReclaimOps: Send + 'static {
+        /// Takes over the reclamation from the other threads.
+        fn attach(&self);
+        /// Hands the reclamation back to the other threads.
+        fn detach(&self);
+        /// Reclaims the garbage every `interval` until `stop` is set.
+        fn run(&self, stop: &AtomicBool, interval: Duration);
+    }
+}
+
+use sealed::ReclaimOps;
+
+/// A collector or a domain whose garbage can be reclaimed by a [`Reclaimer`].
+///
+/// This trait is sealed. It is implemented by [`Collector`] and `&'static Domain`.
+pub trait Reclaimable: ReclaimOps {}
+
+impl ReclaimOps for Collector {
+    fn attach(&self) {
+        self.global.attach_reclaimer();
+    }
+
+    fn detach(&self) {
+        self.global.detach_reclaimer();
+    }
+
+    fn run(&self, stop: &AtomicBool, interval: Duration) {
+        let handle = self.register();
+        loop {
+            let stopping = stop.load(Ordering::Acquire);
+            {
+                // Each pass repins, so that the global epoch advances across passes.
+                let guard = handle.pin();
+                guard.flush();
+                self.global.collect_all(&guard);
+            }
+            if stopping {
+                return;
+            }
+            thread::park_timeout(interval);
+        }
+    }
+}
+
+impl Reclaimable for Collector {}
+
+impl ReclaimOps for &'static Domain {
+    fn attach(&self) {
+        self.attach_reclaimer();
+    }
+
+    fn detach(&self) {
+        self.detach_reclaimer();
+    }
+
+    fn run(&self, stop: &AtomicBool, interval: Duration) {
+        let thread = Thread::new(self);
+        loop {
+            let stopping = stop.load(Ordering::Acquire);
+            thread.eager_reclaim();
+            if stopping {
+                return;
+            }
+            thread::park_timeout(interval);
+        }
+    }
+}
+
+impl Reclaimable for &'static Domain {}
+
+/// A target attached to a reclaimer until dropped, even if the reclaimer fails to spawn or panics.
+struct Attached<R: Reclaimable>(R);
+
+impl<R: Reclaimable> Attached<R> {
+    fn new(target: R) -> Self {
+        target.attach();
+        Self(target)
+    }
+}
+
+impl<R: Reclaimable> Drop for Attached<R> {
+    fn drop(&mut self) {
+        self.0.detach();
+    }
+}
+
+/// A background thread which reclaims the garbage of a collector or a domain.
+///
+/// For an EBR [`Collector`], the reclaimer advances the global epoch and collects the expired bags
+/// of the global queue. For an HP [`Domain`], it scans the hazard pointers and reclaims the
+/// unprotected objects of the domain. Meanwhile, the other threads stop reclaiming periodically,
+/// so that destructors do not run on their critical paths.
+///
+/// The reclaimer stops, after a last pass, when it is dropped.
+pub struct Reclaimer {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Reclaimer {
+    /// The default interval between the passes of a reclaimer.
+    pub const DEFAULT_INTERVAL: Duration = Duration::from_millis(1);
+
+    /// Spawns a reclaimer of `target` with [`Self::DEFAULT_INTERVAL`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the thread can not be spawned.
+    pub fn spawn<R: Reclaimable>(target: R) -> Self {
+        Self::with_interval(target, Self::DEFAULT_INTERVAL).expect("failed to spawn a reclaimer")
+    }
+
+    /// Spawns a reclaimer of `target`, which reclaims every `interval`.
+    pub fn with_interval<R: Reclaimable>(target: R, interval: Duration) -> io::Result<Self> {
+        let stop = Arc::new(AtomicBool::new(false));
+        let target = Attached::new(target);
+        let thread = {
+            let stop = stop.clone();
+            thread::Builder::new()
+                .name("cdrc-reclaimer".into())
+                .spawn(move || target.0.run(&stop, interval))?
+        };
+        Ok(Self {
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    /// Stops the reclaimer and waits for its last pass.
+    pub fn stop(mut self) {
+        self.join();
+    }
+
+    fn join(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            self.stop.store(true, Ordering::Release);
+            thread.thread().unpark();
+            // A panic of a destructor is already handled by the panic policy.
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for Reclaimer {
+    fn drop(&mut self) {
+        self.join();
+    }
+}
+
+impl fmt::Debug for Reclaimer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Reclaimer")
+            .field(
+                "thread",
+                &self.thread.as_ref().map(|thread| thread.thread().id()),
+            )
+            .finish_non_exhaustive()
+    }
+}
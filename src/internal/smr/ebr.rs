@@ -1,27 +1,35 @@
+use std::cell::RefCell;
 use std::mem;
+use std::ops::Range;
+use std::time::{Duration, Instant};
 
-use atomic::Ordering;
+use atomic::{Atomic, Ordering};
 
-use super::ebr_impl::{pin, Guard};
-use crate::internal::utils::Counted;
-use crate::internal::{Acquired, Cs, RetireType, TaggedCnt};
+use super::ebr_impl::{self, pin, Guard};
+use crate::internal::leak;
+use crate::internal::observer::notify;
+use crate::internal::pool;
+use crate::internal::stats::Event;
+use crate::internal::unwind;
+use crate::internal::utils::{CountedObject, EjectAction};
+use crate::internal::{Acquired, Cs, RetireType, Tagged};
 
 /// A tagged pointer which is pointing a `CountedObjPtr<T>`.
 ///
 /// We may want to use `crossbeam_ebr::Shared` as a `Acquired`,
 /// but trait interfaces can be complicated because `crossbeam_ebr::Shared`
 /// requires to specify a lifetime specifier.
-pub struct AcquiredEBR<T>(TaggedCnt<T>);
+pub struct AcquiredEBR<T>(Tagged<T>);
 
 impl<T> Acquired<T> for AcquiredEBR<T> {
     #[inline(always)]
-    fn as_ptr(&self) -> TaggedCnt<T> {
+    fn as_ptr(&self) -> Tagged<T> {
         self.0
     }
 
     #[inline(always)]
     fn null() -> Self {
-        Self(TaggedCnt::null())
+        Self(Tagged::null())
     }
 
     #[inline(always)]
@@ -41,7 +49,7 @@ impl<T> Acquired<T> for AcquiredEBR<T> {
 
     #[inline]
     fn clear(&mut self) {
-        self.0 = TaggedCnt::null();
+        self.0 = Tagged::null();
     }
 
     #[inline]
@@ -55,6 +63,32 @@ impl<T> Acquired<T> for AcquiredEBR<T> {
     }
 }
 
+/// A release of a reference count, which is queued while the current thread is ejecting.
+struct Release {
+    ptr: *mut u8,
+    size: usize,
+    ret_type: RetireType,
+    eject: unsafe fn(*mut u8, RetireType),
+}
+
+/// The ongoing ejection of the current thread.
+struct Ejection {
+    /// The address range of the object being ejected.
+    object: Range<usize>,
+    /// The releases queued meanwhile.
+    releases: Vec<Release>,
+}
+
+thread_local! {
+    /// The ongoing ejection of the current thread, or `None` if the thread is not ejecting.
+    ///
+    /// Ejecting an object may destroy it, which drops its links and thus releases the objects
+    /// they point to, and so on. Such chained releases are queued here and processed one by one
+    /// by the outermost ejection, so that destroying a long chain of objects does not recurse
+    /// through the whole chain.
+    static EJECTION: RefCell<Option<Ejection>> = const { RefCell::new(None) };
+}
+
 pub struct CsEBR {
     guard: Option<Guard>,
 }
@@ -66,6 +100,13 @@ impl From<Guard> for CsEBR {
     }
 }
 
+impl From<&ebr_impl::Participant> for CsEBR {
+    #[inline(always)]
+    fn from(participant: &ebr_impl::Participant) -> Self {
+        Self::from(participant.pin())
+    }
+}
+
 impl Cs for CsEBR {
     type RawShield<T> = AcquiredEBR<T>;
 
@@ -75,21 +116,23 @@ impl Cs for CsEBR {
     }
 
     #[inline(always)]
-    fn create_object<T>(obj: T) -> *mut Counted<T> {
-        let obj = Counted::new(obj);
-        Box::into_raw(Box::new(obj))
+    fn create_object<O: CountedObject + Send>(obj: O::Target) -> *mut O {
+        ebr_impl::record(Event::Allocated, 1);
+        let ptr = pool::alloc(O::new(obj));
+        leak::track::<Self, O>(ptr);
+        ptr
     }
 
     #[inline(always)]
-    fn reserve<T>(&self, ptr: TaggedCnt<T>, shield: &mut Self::RawShield<T>) {
+    fn reserve<O>(&self, ptr: Tagged<O>, shield: &mut Self::RawShield<O>) {
         *shield = AcquiredEBR(ptr);
     }
 
     #[inline(always)]
-    fn protect_snapshot<T>(
+    fn protect_snapshot<O: CountedObject>(
         &self,
-        link: &atomic::Atomic<TaggedCnt<T>>,
-        shield: &mut Self::RawShield<T>,
+        link: &atomic::Atomic<Tagged<O>>,
+        shield: &mut Self::RawShield<O>,
     ) -> bool {
         let ptr = link.load(Ordering::Acquire);
         if !ptr.is_null() && unsafe { ptr.deref() }.ref_count() == 0 {
@@ -102,21 +145,76 @@ impl Cs for CsEBR {
     }
 
     #[inline(always)]
-    unsafe fn own_object<T>(ptr: *mut Counted<T>) -> Counted<T> {
-        *Box::from_raw(ptr)
+    unsafe fn own_object<O: CountedObject>(ptr: *mut O) -> O {
+        ebr_impl::record(Event::Destroyed, 1);
+        leak::untrack(ptr);
+        pool::take(ptr)
     }
 
     #[inline(always)]
-    unsafe fn retire<T>(&self, ptr: *mut Counted<T>, ret_type: RetireType) {
+    unsafe fn retire<O: CountedObject + Send>(&self, ptr: *mut O, ret_type: RetireType) {
         debug_assert!(!ptr.is_null());
+        notify!(on_retire(ptr as *const u8, ret_type));
         let cnt = &mut *ptr;
         if let Some(guard) = &self.guard {
+            let size = mem::size_of::<O>() as u64;
+            ebr_impl::record(Event::Retired, 1);
+            ebr_impl::record(Event::RetiredBytes, size);
             guard.defer_unchecked(move || {
-                let inner_guard = Self::unprotected();
-                inner_guard.eject(cnt, ret_type);
+                Self::eject_iteratively(cnt, ret_type);
+                ebr_impl::record(Event::Reclaimed, 1);
+                ebr_impl::record(Event::ReclaimedBytes, size);
             });
         } else {
-            self.eject(cnt, ret_type);
+            Self::eject_iteratively(cnt, ret_type);
+        }
+    }
+
+    #[inline]
+    unsafe fn decrement_ref_cnt<O: CountedObject + Send>(&self, cnt: &mut O) {
+        debug_assert!(cnt.ref_count() >= 1);
+        match cnt.release_ref() {
+            EjectAction::Nothing => {}
+            EjectAction::Delay => self.retire(cnt, RetireType::Dispose),
+            EjectAction::Destroy => {
+                // `release_ref` has already disposed the object.
+                ebr_impl::record(Event::Disposed, 1);
+                notify!(on_dispose(cnt as *mut O as *const u8));
+                self.destroy(cnt);
+            }
+        }
+    }
+
+    #[inline]
+    unsafe fn dispose<O: CountedObject>(&self, cnt: &mut O) {
+        debug_assert!(cnt.ref_count() == 0);
+        ebr_impl::record(Event::Disposed, 1);
+        cnt.dispose();
+        notify!(on_dispose(cnt as *mut O as *const u8));
+        if cnt.release_weak() {
+            self.destroy(cnt);
+        }
+    }
+
+    #[inline]
+    unsafe fn release_link_ref_cnt<O: CountedObject + Send>(
+        &self,
+        link: &Atomic<Tagged<O>>,
+        cnt: &mut O,
+    ) {
+        if !Self::queue_release(link, cnt, RetireType::DecrementStrongCount) {
+            self.delayed_decrement_ref_cnt(cnt);
+        }
+    }
+
+    #[inline]
+    unsafe fn release_link_weak_cnt<O: CountedObject + Send>(
+        &self,
+        link: &Atomic<Tagged<O>>,
+        cnt: &mut O,
+    ) {
+        if !Self::queue_release(link, cnt, RetireType::DecrementWeakCount) {
+            self.delayed_decrement_weak_cnt(cnt);
         }
     }
 
@@ -144,4 +242,123 @@ impl Cs for CsEBR {
             guard.flush();
         }
     }
+
+    fn synchronize() {
+        ebr_impl::synchronize();
+    }
+
+    fn barrier() {
+        // Ejecting an object may retire the objects it points to, so repeat until a round retires
+        // nothing more.
+        loop {
+            let retired = ebr_impl::thread_stats().retired;
+            ebr_impl::barrier();
+            if ebr_impl::thread_stats().retired == retired {
+                return;
+            }
+        }
+    }
+
+    fn try_barrier(timeout: Duration) -> bool {
+        let deadline = Instant::now().checked_add(timeout);
+        loop {
+            let retired = ebr_impl::thread_stats().retired;
+            let remaining = deadline.map_or(Duration::MAX, |deadline| {
+                deadline.saturating_duration_since(Instant::now())
+            });
+            if !ebr_impl::try_barrier(remaining) {
+                return false;
+            }
+            if ebr_impl::thread_stats().retired == retired {
+                return true;
+            }
+        }
+    }
+}
+
+impl CsEBR {
+    /// Ejects `cnt`, and then processes the releases queued meanwhile until none is left.
+    ///
+    /// If the current thread is already ejecting, `cnt` is ejected right away, and the releases
+    /// it causes are processed by the outermost ejection. If the thread-local state is already
+    /// destroyed, releases are not queued but delayed as usual.
+    unsafe fn eject_iteratively<O: CountedObject + Send>(cnt: &mut O, ret_type: RetireType) {
+        let outermost = EJECTION
+            .try_with(|ejection| {
+                let mut ejection = ejection.borrow_mut();
+                ejection.is_none() && {
+                    *ejection = Some(Ejection {
+                        object: 0..0,
+                        releases: Vec::new(),
+                    });
+                    true
+                }
+            })
+            .unwrap_or(false);
+        let ptr = cnt as *mut O as *mut u8;
+        if !outermost {
+            Self::eject_erased::<O>(ptr, ret_type);
+            return;
+        }
+
+        let mut release = Some(Release {
+            ptr,
+            size: mem::size_of::<O>(),
+            ret_type,
+            eject: Self::eject_erased::<O>,
+        });
+        while let Some(Release {
+            ptr,
+            size,
+            ret_type,
+            eject,
+        }) = release
+        {
+            let start = ptr as usize;
+            EJECTION.with(|ejection| {
+                ejection.borrow_mut().as_mut().unwrap().object = start..start + size;
+            });
+            // Isolate each ejection, so that a panicking destructor can not leave releases behind
+            // in the queue.
+            unwind::isolate(|| eject(ptr, ret_type));
+            release =
+                EJECTION.with(|ejection| ejection.borrow_mut().as_mut().unwrap().releases.pop());
+        }
+        EJECTION.with(|ejection| *ejection.borrow_mut() = None);
+    }
+
+    unsafe fn eject_erased<O: CountedObject + Send>(ptr: *mut u8, ret_type: RetireType) {
+        Self::unprotected().eject(&mut *(ptr as *mut O), ret_type);
+    }
+
+    /// Queues a release caused by dropping `link`, if the link resides in the object which the
+    /// current thread is ejecting.
+    ///
+    /// Such an object is ejected after a grace period, so every thread which could have traversed
+    /// the link has been unpinned since then, and the count may be released without waiting for
+    /// another one. Other links dropped meanwhile, e.g. the ones in a structure whose last owner
+    /// is dropped by a destructor, may still be traversed by pinned threads.
+    ///
+    /// Returns `false` if nothing is queued.
+    unsafe fn queue_release<O: CountedObject + Send>(
+        link: &Atomic<Tagged<O>>,
+        cnt: &mut O,
+        ret_type: RetireType,
+    ) -> bool {
+        let link = link as *const _ as usize;
+        EJECTION
+            .try_with(|ejection| match ejection.borrow_mut().as_mut() {
+                Some(ejection) if ejection.object.contains(&link) => {
+                    ejection.releases.push(Release {
+                        ptr: cnt as *mut O as *mut u8,
+                        size: mem::size_of::<O>(),
+                        ret_type,
+                        eject: Self::eject_erased::<O>,
+                    });
+                    true
+                }
+                _ => false,
+            })
+            .unwrap_or(false)
+    }
 }
@@ -1,11 +1,17 @@
 /// Epoch-based garbage collector.
 use core::fmt;
 use core::sync::atomic::Ordering;
+use core::time::Duration;
 
+use super::config::Config;
 use super::guard::Guard;
 use super::internal::{Global, Local};
+use super::stall::StalledParticipant;
 use super::Epoch;
+use crate::internal::histogram::LatencyHistogram;
+use crate::internal::stats::Stats;
 use std::sync::Arc;
+use std::time::Instant;
 
 /// An epoch-based garbage collector.
 pub struct Collector {
@@ -17,9 +23,7 @@ unsafe impl Sync for Collector {}
 
 impl Default for Collector {
     fn default() -> Self {
-        Self {
-            global: Arc::new(Global::new()),
-        }
+        Self::with_config(Config::default())
     }
 }
 
@@ -29,6 +33,26 @@ impl Collector {
         Self::default()
     }
 
+    /// Creates a new collector with the given configuration.
+    pub fn with_config(config: Config) -> Self {
+        Self {
+            global: Arc::new(Global::new(config)),
+        }
+    }
+
+    /// Returns the current configuration.
+    pub fn config(&self) -> Config {
+        self.global.config.load()
+    }
+
+    /// Replaces the configuration.
+    ///
+    /// Participants observe the new configuration eventually. A new bag capacity applies to the
+    /// bags created afterwards.
+    pub fn set_config(&self, config: Config) {
+        self.global.config.store(config);
+    }
+
     /// Registers a new handle for the collector.
     pub fn register(&self) -> LocalHandle {
         Local::register(self)
@@ -44,6 +68,49 @@ impl Collector {
     pub fn is_global_queue_empty(&self) -> bool {
         self.global.is_global_queue_empty()
     }
+
+    /// Returns the histogram of the latencies from deferring functions to executing them.
+    ///
+    /// The functions in a bag are accounted together with the age of the bag, which is an upper
+    /// bound of their latencies.
+    pub fn latency_histogram(&self) -> LatencyHistogram {
+        self.global.latency.snapshot()
+    }
+
+    /// Returns the participants which have kept the global epoch from advancing for longer than
+    /// [`Config::stall_threshold`].
+    ///
+    /// Participants are examined by attempts to advance the global epoch, so a stall is detected
+    /// only while the other participants keep trying.
+    pub fn stalled_participants(&self) -> Vec<StalledParticipant> {
+        self.global.stalled_participants()
+    }
+
+    /// Sets the function called when a participant is found stalled, once per stall.
+    ///
+    /// The handler is called by the thread which attempted to advance the global epoch. Use
+    /// [`log_stall`](super::log_stall) to print stalled participants to the standard error.
+    pub fn set_stall_handler<F>(&self, handler: F)
+    where
+        F: Fn(&StalledParticipant) + Send + Sync + 'static,
+    {
+        *self.global.stall_handler.lock().unwrap() = Some(Arc::new(handler));
+    }
+
+    /// Removes the stall handler.
+    pub fn clear_stall_handler(&self) {
+        *self.global.stall_handler.lock().unwrap() = None;
+    }
+
+    /// Returns the number of participants registered in the collector.
+    pub fn num_participants(&self) -> usize {
+        self.global.num_participants()
+    }
+
+    /// Returns the statistics of all participants, including the ones which have left.
+    pub fn stats(&self) -> Stats {
+        self.global.stats().into()
+    }
 }
 
 impl Clone for Collector {
@@ -92,6 +159,65 @@ impl LocalHandle {
     pub fn collector(&self) -> &Collector {
         unsafe { (*self.local).collector() }
     }
+
+    /// Creates another handle of the same participant.
+    pub(crate) fn share(&self) -> Self {
+        unsafe { (*self.local).acquire_handle() };
+        Self { local: self.local }
+    }
+
+    /// Returns the statistics of the participant of this handle.
+    pub fn stats(&self) -> Stats {
+        unsafe { (*self.local).stats().load() }.into()
+    }
+
+    /// Blocks until every participant pinned before the call has been unpinned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the handle is pinned. The call never returns if another participant of the
+    /// current thread is pinned.
+    pub fn synchronize(&self) {
+        assert!(!self.is_pinned(), "`synchronize` called while pinned");
+        let local = unsafe { &*self.local };
+        local.global().synchronize(local, None);
+    }
+
+    /// Blocks until every function deferred to the collector before the call has been executed.
+    ///
+    /// The other participants hand their deferred functions over the next time they are pinned,
+    /// so the call waits for each of them to be pinned again, or to be unregistered. Hence it
+    /// blocks as long as a registered participant stays idle, e.g., the participant of a parked
+    /// thread. Use [`try_barrier`] to bound the wait.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the handle is pinned. The call never returns if another participant of the
+    /// current thread is pinned, or holds deferred functions.
+    ///
+    /// [`try_barrier`]: LocalHandle::try_barrier
+    pub fn barrier(&self) {
+        assert!(!self.is_pinned(), "`barrier` called while pinned");
+        let local = unsafe { &*self.local };
+        local.global().barrier(local, None);
+    }
+
+    /// Like [`barrier`], but gives up once `timeout` has elapsed.
+    ///
+    /// Returns `true` if every function deferred before the call has been executed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the handle is pinned.
+    ///
+    /// [`barrier`]: LocalHandle::barrier
+    pub fn try_barrier(&self, timeout: Duration) -> bool {
+        assert!(!self.is_pinned(), "`try_barrier` called while pinned");
+        let local = unsafe { &*self.local };
+        local
+            .global()
+            .barrier(local, Instant::now().checked_add(timeout))
+    }
 }
 
 impl Drop for LocalHandle {
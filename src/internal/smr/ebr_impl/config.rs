@@ -0,0 +1,315 @@
+//! Tuning parameters of a collector.
+
+use core::sync::atomic::{AtomicU64, AtomicU8, AtomicUsize, Ordering};
+use core::time::Duration;
+
+use crate::internal::config::{
+    check_positive, nanos, AtomicGarbageLimit, AtomicReclaimBudget, ConfigError, GarbageLimit,
+    ReclaimBudget,
+};
+
+/// The registry of participants which is scanned by the attempts to advance the global epoch.
+///
+/// Both registries are maintained for every participant, so the registry of a collector can be
+/// changed at any time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Registry {
+    /// The intrusive linked list of participants.
+    #[default]
+    List,
+    /// A growable array of cache-padded local epochs, which is scanned without chasing pointers.
+    /// It is faster with many participants, but the array does not shrink when they leave.
+    Array,
+}
+
+/// The tuning parameters of a [`Collector`](super::Collector).
+///
+/// A `Config` is created by a [`ConfigBuilder`], which validates the parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Config {
+    bag_capacity: usize,
+    collect_interval: usize,
+    advance_interval: usize,
+    manual_collect_interval: usize,
+    stall_threshold: Duration,
+    garbage_limit: Option<GarbageLimit>,
+    reclaim_budget: Option<ReclaimBudget>,
+    registry: Registry,
+}
+
+impl Config {
+    /// Returns the default configuration.
+    pub const fn new() -> Self {
+        // Makes it more likely to trigger any potential data races.
+        #[cfg(any(crossbeam_sanitize, miri))]
+        const BAG_CAPACITY: usize = 4;
+        #[cfg(not(any(crossbeam_sanitize, miri)))]
+        const BAG_CAPACITY: usize = 64;
+
+        Self {
+            bag_capacity: BAG_CAPACITY,
+            collect_interval: BAG_CAPACITY,
+            advance_interval: BAG_CAPACITY * 2,
+            manual_collect_interval: 128,
+            stall_threshold: Duration::from_secs(1),
+            garbage_limit: None,
+            reclaim_budget: None,
+            registry: Registry::List,
+        }
+    }
+
+    /// Returns a builder initialized with the default configuration.
+    pub fn builder() -> ConfigBuilder {
+        Self::new().into_builder()
+    }
+
+    /// Returns a builder initialized with this configuration.
+    pub fn into_builder(self) -> ConfigBuilder {
+        ConfigBuilder { config: self }
+    }
+
+    /// The maximum number of deferred functions in a thread-local bag. A full bag is pushed to the
+    /// global queue.
+    pub fn bag_capacity(&self) -> usize {
+        self.bag_capacity
+    }
+
+    /// The number of deferrals and flushes of a participant between collections.
+    pub fn collect_interval(&self) -> usize {
+        self.collect_interval
+    }
+
+    /// The number of deferrals and flushes of a participant between attempts to advance the
+    /// global epoch.
+    pub fn advance_interval(&self) -> usize {
+        self.advance_interval
+    }
+
+    /// The number of calls to `Guard::incr_manual_collection` between flushes.
+    pub fn manual_collect_interval(&self) -> usize {
+        self.manual_collect_interval
+    }
+
+    /// How long a participant may keep the global epoch from advancing before it is considered
+    /// stalled.
+    pub fn stall_threshold(&self) -> Duration {
+        self.stall_threshold
+    }
+
+    /// The limit on the number of deferred functions in the global queue, if any.
+    pub fn garbage_limit(&self) -> Option<GarbageLimit> {
+        self.garbage_limit
+    }
+
+    /// The bound on the destructor work of a collection, if any.
+    pub fn reclaim_budget(&self) -> Option<ReclaimBudget> {
+        self.reclaim_budget
+    }
+
+    /// The registry of participants scanned to advance the global epoch.
+    pub fn registry(&self) -> Registry {
+        self.registry
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A builder of a [`Config`].
+#[derive(Debug, Clone)]
+pub struct ConfigBuilder {
+    config: Config,
+}
+
+impl ConfigBuilder {
+    /// Sets [`Config::bag_capacity`].
+    pub fn bag_capacity(mut self, capacity: usize) -> Self {
+        self.config.bag_capacity = capacity;
+        self
+    }
+
+    /// Sets [`Config::collect_interval`].
+    pub fn collect_interval(mut self, interval: usize) -> Self {
+        self.config.collect_interval = interval;
+        self
+    }
+
+    /// Sets [`Config::advance_interval`].
+    pub fn advance_interval(mut self, interval: usize) -> Self {
+        self.config.advance_interval = interval;
+        self
+    }
+
+    /// Sets [`Config::manual_collect_interval`].
+    pub fn manual_collect_interval(mut self, interval: usize) -> Self {
+        self.config.manual_collect_interval = interval;
+        self
+    }
+
+    /// Sets [`Config::stall_threshold`].
+    pub fn stall_threshold(mut self, threshold: Duration) -> Self {
+        self.config.stall_threshold = threshold;
+        self
+    }
+
+    /// Sets [`Config::garbage_limit`].
+    pub fn garbage_limit(mut self, limit: Option<GarbageLimit>) -> Self {
+        self.config.garbage_limit = limit;
+        self
+    }
+
+    /// Sets [`Config::reclaim_budget`].
+    pub fn reclaim_budget(mut self, budget: Option<ReclaimBudget>) -> Self {
+        self.config.reclaim_budget = budget;
+        self
+    }
+
+    /// Sets [`Config::registry`].
+    pub fn registry(mut self, registry: Registry) -> Self {
+        self.config.registry = registry;
+        self
+    }
+
+    /// Validates the parameters and returns the configuration.
+    pub fn build(self) -> Result<Config, ConfigError> {
+        let config = self.config;
+        check_positive("bag_capacity", config.bag_capacity)?;
+        check_positive("collect_interval", config.collect_interval)?;
+        check_positive("advance_interval", config.advance_interval)?;
+        check_positive("manual_collect_interval", config.manual_collect_interval)?;
+        if config.stall_threshold.is_zero() {
+            return Err(ConfigError::Zero("stall_threshold"));
+        }
+        ReclaimBudget::check(config.reclaim_budget)?;
+        Ok(config)
+    }
+}
+
+/// A [`Config`] which can be read and replaced concurrently.
+///
+/// Each parameter is read independently, so a reader racing with `store` may observe a mix of the
+/// old and the new parameters. Any such mix is a valid configuration.
+#[derive(Debug)]
+pub(crate) struct AtomicConfig {
+    bag_capacity: AtomicUsize,
+    collect_interval: AtomicUsize,
+    advance_interval: AtomicUsize,
+    manual_collect_interval: AtomicUsize,
+    /// In nanoseconds.
+    stall_threshold: AtomicU64,
+    pub(crate) garbage_limit: AtomicGarbageLimit,
+    pub(crate) reclaim_budget: AtomicReclaimBudget,
+    registry: AtomicU8,
+}
+
+impl AtomicConfig {
+    pub(crate) const fn new(config: Config) -> Self {
+        Self {
+            bag_capacity: AtomicUsize::new(config.bag_capacity),
+            collect_interval: AtomicUsize::new(config.collect_interval),
+            advance_interval: AtomicUsize::new(config.advance_interval),
+            manual_collect_interval: AtomicUsize::new(config.manual_collect_interval),
+            stall_threshold: AtomicU64::new(nanos(config.stall_threshold)),
+            garbage_limit: AtomicGarbageLimit::new(config.garbage_limit),
+            reclaim_budget: AtomicReclaimBudget::new(config.reclaim_budget),
+            registry: AtomicU8::new(config.registry as u8),
+        }
+    }
+
+    pub(crate) fn load(&self) -> Config {
+        Config {
+            bag_capacity: self.bag_capacity(),
+            collect_interval: self.collect_interval(),
+            advance_interval: self.advance_interval(),
+            manual_collect_interval: self.manual_collect_interval(),
+            stall_threshold: self.stall_threshold(),
+            garbage_limit: self.garbage_limit.load(),
+            reclaim_budget: self.reclaim_budget.load(),
+            registry: self.registry(),
+        }
+    }
+
+    pub(crate) fn store(&self, config: Config) {
+        self.bag_capacity
+            .store(config.bag_capacity, Ordering::Relaxed);
+        self.collect_interval
+            .store(config.collect_interval, Ordering::Relaxed);
+        self.advance_interval
+            .store(config.advance_interval, Ordering::Relaxed);
+        self.manual_collect_interval
+            .store(config.manual_collect_interval, Ordering::Relaxed);
+        self.stall_threshold
+            .store(nanos(config.stall_threshold), Ordering::Relaxed);
+        self.garbage_limit.store(config.garbage_limit);
+        self.reclaim_budget.store(config.reclaim_budget);
+        self.registry
+            .store(config.registry as u8, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub(crate) fn bag_capacity(&self) -> usize {
+        self.bag_capacity.load(Ordering::Relaxed)
+    }
+
+    #[inline]
+    pub(crate) fn collect_interval(&self) -> usize {
+        self.collect_interval.load(Ordering::Relaxed)
+    }
+
+    #[inline]
+    pub(crate) fn advance_interval(&self) -> usize {
+        self.advance_interval.load(Ordering::Relaxed)
+    }
+
+    #[inline]
+    pub(crate) fn manual_collect_interval(&self) -> usize {
+        self.manual_collect_interval.load(Ordering::Relaxed)
+    }
+
+    #[inline]
+    pub(crate) fn stall_threshold(&self) -> Duration {
+        Duration::from_nanos(self.stall_threshold.load(Ordering::Relaxed))
+    }
+
+    #[inline]
+    pub(crate) fn registry(&self) -> Registry {
+        if self.registry.load(Ordering::Relaxed) == Registry::Array as u8 {
+            Registry::Array
+        } else {
+            Registry::List
+        }
+    }
+}
+
+#[cfg(all(test, not(crossbeam_loom)))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validation() {
+        assert_eq!(Config::builder().build(), Ok(Config::default()));
+        assert_eq!(
+            Config::builder().collect_interval(0).build(),
+            Err(ConfigError::Zero("collect_interval"))
+        );
+        assert_eq!(
+            Config::builder().stall_threshold(Duration::ZERO).build(),
+            Err(ConfigError::Zero("stall_threshold"))
+        );
+
+        let config = Config::builder().bag_capacity(16).build().unwrap();
+        assert_eq!(config.bag_capacity(), 16);
+        assert_eq!(config.advance_interval(), Config::new().advance_interval());
+
+        let atomic = AtomicConfig::new(Config::new());
+        atomic.store(config);
+        assert_eq!(atomic.load(), config);
+
+        let config = Config::builder().registry(Registry::Array).build().unwrap();
+        atomic.store(config);
+        assert_eq!(atomic.load(), config);
+    }
+}
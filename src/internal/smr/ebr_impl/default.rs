@@ -2,11 +2,17 @@
 //!
 //! For each thread, a participant is lazily initialized on its first use, when the current thread
 //! is registered in the default collector.  If initialized, the thread's participant will get
-//! destructed on thread exit, which in turn unregisters the thread.
+//! destructed on thread exit, which in turn unregisters the thread. A [`Participant`] registers and
+//! unregisters the current thread explicitly instead.
+
+use core::cell::RefCell;
+use core::time::Duration;
+use std::thread::AccessError;
 
 use super::collector::{Collector, LocalHandle};
 use super::guard::Guard;
 use super::sync::once_lock::OnceLock;
+use crate::internal::stats::{Event, Stats};
 
 fn collector() -> &'static Collector {
     /// The global data for the default garbage collector.
@@ -15,8 +21,63 @@ fn collector() -> &'static Collector {
 }
 
 thread_local! {
-    /// The per-thread participant for the default garbage collector.
-    static HANDLE: LocalHandle = collector().register();
+    /// The per-thread participant for the default garbage collector, or `None` if the current
+    /// thread is not registered.
+    static HANDLE: RefCell<Option<LocalHandle>> = const { RefCell::new(None) };
+}
+
+/// A registration of the current thread in the default collector.
+///
+/// A thread is registered lazily on its first use of the collector, and unregistered when it
+/// exits. A participant registers the current thread up front instead, and unregisters it when
+/// dropped, e.g., when a worker of a thread pool parks, so that attempts to advance the global
+/// epoch no longer examine the thread. The thread is registered again on its next use.
+#[derive(Debug)]
+pub struct Participant {
+    handle: LocalHandle,
+}
+
+impl Participant {
+    /// Registers the current thread in the default collector, unless it is already registered.
+    pub fn register() -> Self {
+        Self {
+            handle: with_handle(|handle| handle.share()),
+        }
+    }
+
+    /// Returns `true` if the current thread is registered in the default collector.
+    pub fn is_registered() -> bool {
+        HANDLE
+            .try_with(|slot| slot.borrow().is_some())
+            .unwrap_or(false)
+    }
+
+    /// Pins the current thread.
+    #[inline]
+    pub fn pin(&self) -> Guard {
+        self.handle.pin()
+    }
+
+    /// Unregisters the current thread, as dropping the participant does.
+    ///
+    /// If the thread is pinned, it leaves the collector once all of its guards are dropped.
+    pub fn unregister(self) {}
+}
+
+impl Drop for Participant {
+    fn drop(&mut self) {
+        // Leave the slot alone if the thread has been registered again meanwhile.
+        let handle = HANDLE.try_with(|slot| {
+            let mut slot = slot.try_borrow_mut().ok()?;
+            if slot.as_ref()?.local == self.handle.local {
+                slot.take()
+            } else {
+                None
+            }
+        });
+        // Dropped outside of the borrow, as it may finalize the participant.
+        drop(handle);
+    }
 }
 
 /// Pins the current thread.
@@ -36,14 +97,127 @@ pub fn default_collector() -> &'static Collector {
     collector()
 }
 
+/// Blocks until every participant of the default collector pinned before the call has been
+/// unpinned. See [`LocalHandle::synchronize`].
+pub fn synchronize() {
+    with_handle(|handle| handle.synchronize())
+}
+
+/// Blocks until every function deferred to the default collector before the call has been
+/// executed. See [`LocalHandle::barrier`].
+pub fn barrier() {
+    with_handle(|handle| handle.barrier())
+}
+
+/// Like [`barrier`], but gives up once `timeout` has elapsed. See [`LocalHandle::try_barrier`].
+pub fn try_barrier(timeout: Duration) -> bool {
+    with_handle(|handle| handle.try_barrier(timeout))
+}
+
+/// Sets the capacity of thread-local bags of the default collector.
+///
+/// # Panics
+///
+/// Panics if `max_objects` is zero.
+#[deprecated(note = "Use `Collector::set_config` with `ConfigBuilder::bag_capacity` instead")]
+pub fn set_bag_capacity(max_objects: usize) {
+    let collector = collector();
+    let config = collector
+        .config()
+        .into_builder()
+        .bag_capacity(max_objects)
+        .build()
+        .expect("invalid EBR configuration");
+    collector.set_config(config);
+}
+
+/// Sets the manual collection interval of the default collector.
+///
+/// # Panics
+///
+/// Panics if `interval` is zero.
+#[deprecated(
+    note = "Use `Collector::set_config` with `ConfigBuilder::manual_collect_interval` instead"
+)]
+pub fn set_manual_collection_interval(interval: usize) {
+    let collector = collector();
+    let config = collector
+        .config()
+        .into_builder()
+        .manual_collect_interval(interval)
+        .build()
+        .expect("invalid EBR configuration");
+    collector.set_config(config);
+}
+
+/// Returns the statistics of the current thread's participant in the default collector.
+///
+/// If the current thread is not registered, or its participant is already destroyed, the
+/// statistics are empty. Reading never registers the thread.
+pub fn thread_stats() -> Stats {
+    HANDLE
+        .try_with(|slot| match slot.try_borrow().as_deref() {
+            Ok(Some(handle)) => handle.stats(),
+            _ => Stats::default(),
+        })
+        .unwrap_or_default()
+}
+
+/// Records an event of the current thread in the default collector.
+///
+/// If the current thread is not registered, e.g., it has only allocated objects, or its
+/// participant is already destroyed, the event is recorded in the collector itself. Recording
+/// never registers the thread.
+#[inline]
+pub(crate) fn record(event: Event, n: u64) {
+    let recorded = HANDLE
+        .try_with(|slot| match slot.try_borrow().as_deref() {
+            Ok(Some(handle)) => {
+                unsafe { &*handle.local }.stats().add(event, n);
+                true
+            }
+            _ => false,
+        })
+        .unwrap_or(false);
+    if !recorded {
+        collector().global.stats.add_shared(event, n);
+    }
+}
+
 #[inline]
 fn with_handle<F, R>(mut f: F) -> R
 where
     F: FnMut(&LocalHandle) -> R,
 {
-    HANDLE
-        .try_with(|h| f(h))
-        .unwrap_or_else(|_| f(&collector().register()))
+    try_with_handle(&mut f).unwrap_or_else(|_| f(&collector().register()))
+}
+
+/// Calls `f` with the current thread's participant, registering the thread if it is not
+/// registered yet. Fails if `HANDLE` has already been destroyed.
+#[inline]
+fn try_with_handle<F, R>(f: &mut F) -> Result<R, AccessError>
+where
+    F: FnMut(&LocalHandle) -> R,
+{
+    HANDLE.try_with(|slot| {
+        let handle = slot.borrow();
+        match &*handle {
+            Some(handle) => f(handle),
+            None => {
+                drop(handle);
+                f(&register(slot))
+            }
+        }
+    })
+}
+
+/// Registers the current thread, and returns another handle of the participant.
+#[cold]
+fn register(slot: &RefCell<Option<LocalHandle>>) -> LocalHandle {
+    let handle = collector().register();
+    let shared = handle.share();
+    *slot.borrow_mut() = Some(handle);
+    shared
 }
 
 #[cfg(all(test, not(crossbeam_loom)))]
@@ -206,7 +206,7 @@ impl Guard {
     }
 
     /// Increases the manual collection counter, and perform collection if the counter reaches
-    /// the threshold which is set by `set_manual_collection_interval`.
+    /// the manual collection interval of the collector's [`Config`](super::Config).
     pub fn incr_manual_collection(&self) {
         if let Some(local) = unsafe { self.local.as_ref() } {
             local.incr_manual_collection(self);
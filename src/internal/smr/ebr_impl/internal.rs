@@ -39,63 +39,57 @@ use super::primitive::cell::UnsafeCell;
 use super::primitive::sync::atomic;
 use core::cell::Cell;
 use core::mem::{self, ManuallyDrop};
-use core::sync::atomic::{AtomicUsize, Ordering};
+use core::ptr::NonNull;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use core::{fmt, ptr};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Instant;
 
 use crossbeam_utils::CachePadded;
 use memoffset::offset_of;
 
 use super::atomic::{Owned, Shared};
 use super::collector::{Collector, LocalHandle};
+use super::config::{AtomicConfig, Config, Registry};
 use super::deferred::Deferred;
 use super::epoch::{AtomicEpoch, Epoch};
 use super::guard::{unprotected, Guard};
+use super::registry::{EpochSlot, EpochSlots};
+use super::stall::{StallHandler, StalledParticipant};
 use super::sync::list::{Entry, IsElement, IterError, List};
 use super::sync::queue::Queue;
+use crate::internal::config::Budget;
+use crate::internal::histogram::AtomicHistogram;
+use crate::internal::observer::notify;
+use crate::internal::stats::{Counters, Event, Totals};
+use crate::internal::unwind;
 
 #[allow(missing_docs)]
 pub static GLOBAL_GARBAGE_COUNT: AtomicUsize = AtomicUsize::new(0);
 
-/// Maximum number of objects a bag can contain.
-#[cfg(not(any(crossbeam_sanitize, miri)))]
-static mut MAX_OBJECTS: usize = 64;
-// Makes it more likely to trigger any potential data races.
-#[cfg(any(crossbeam_sanitize, miri))]
-static mut MAX_OBJECTS: usize = 4;
-
-static mut MANUAL_EVENTS_BETWEEN_COLLECT: usize = 128;
-
-/// Sets the capacity of thread-local deferred bag.
-///
-/// Note that an update on this capacity may not be reflected immediately to concurrent threads,
-/// because there is no synchronization between reads and writes on the capacity variable.
-pub fn set_bag_capacity(max_objects: usize) {
-    unsafe { MAX_OBJECTS = max_objects };
-}
-
-/// Sets the manual collection interval.
-///
-/// Note that an update on this interval may not be reflected immediately to concurrent threads,
-/// because there is no synchronization between reads and writes on the interval variable.
-pub fn set_manual_collection_interval(interval: usize) {
-    unsafe { MANUAL_EVENTS_BETWEEN_COLLECT = interval };
-}
-
 /// A bag of deferred functions.
-pub(crate) struct Bag(Vec<Deferred>);
+pub(crate) struct Bag {
+    deferreds: Vec<Deferred>,
+    /// When the first deferred function was pushed.
+    since: Option<Instant>,
+}
 
 /// `Bag::try_push()` requires that it is safe for another thread to execute the given functions.
 unsafe impl Send for Bag {}
 
 impl Bag {
-    /// Returns a new, empty bag.
-    pub(crate) fn new() -> Self {
-        Self::default()
+    /// Returns a new, empty bag which can contain `capacity` deferred functions.
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        Bag {
+            deferreds: Vec::with_capacity(capacity),
+            since: None,
+        }
     }
 
     /// Returns `true` if the bag is empty.
     pub(crate) fn is_empty(&self) -> bool {
-        self.0.is_empty()
+        self.deferreds.is_empty()
     }
 
     /// Attempts to insert a deferred function into the bag.
@@ -107,31 +101,44 @@ impl Bag {
     ///
     /// It should be safe for another thread to execute the given function.
     pub(crate) unsafe fn try_push(&mut self, deferred: Deferred) -> Result<(), Deferred> {
-        if self.0.len() < self.0.capacity() {
-            self.0.push(deferred);
+        if self.deferreds.len() < self.deferreds.capacity() {
+            if self.deferreds.is_empty() {
+                self.since = Some(Instant::now());
+            }
+            self.deferreds.push(deferred);
             Ok(())
         } else {
             Err(deferred)
         }
     }
 
+    /// Calls deferred functions as long as `budget` allows, and returns the number of them called.
+    fn call_within(&mut self, budget: &mut Budget) -> usize {
+        let mut called = 0;
+        while !budget.is_exhausted() {
+            let deferred = match self.deferreds.pop() {
+                Some(deferred) => deferred,
+                None => break,
+            };
+            unwind::isolate(|| deferred.call());
+            budget.spend();
+            called += 1;
+        }
+        called
+    }
+
     /// Seals the bag with the given epoch.
     fn seal(self, epoch: Epoch) -> SealedBag {
         SealedBag { epoch, bag: self }
     }
 }
 
-impl Default for Bag {
-    fn default() -> Self {
-        Bag(Vec::with_capacity(unsafe { MAX_OBJECTS }))
-    }
-}
-
 impl Drop for Bag {
     fn drop(&mut self) {
-        // Call all deferred functions.
-        for deferred in self.0.drain(..) {
-            deferred.call();
+        // Call all deferred functions. A panic from one of them must not prevent the others from
+        // being called.
+        for deferred in self.deferreds.drain(..) {
+            unwind::isolate(|| deferred.call());
         }
     }
 }
@@ -139,12 +146,14 @@ impl Drop for Bag {
 // can't #[derive(Debug)] because Debug is not implemented for arrays 64 items long
 impl fmt::Debug for Bag {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("Bag").field("deferreds", &self.0).finish()
+        f.debug_struct("Bag")
+            .field("deferreds", &self.deferreds)
+            .finish()
     }
 }
 
 /// A pair of an epoch and a bag.
-#[derive(Default, Debug)]
+#[derive(Debug)]
 struct SealedBag {
     epoch: Epoch,
     bag: Bag,
@@ -169,11 +178,39 @@ pub(crate) struct Global {
     /// The intrusive linked list of `Local`s.
     locals: List<Local>,
 
+    /// The slots of the local epochs of the participants.
+    slots: EpochSlots,
+
     /// The global queue of bags of deferred functions.
     queue: Queue<SealedBag>,
 
     /// The global epoch.
     pub(crate) epoch: CachePadded<AtomicEpoch>,
+
+    /// The tuning parameters.
+    pub(crate) config: AtomicConfig,
+
+    /// The statistics of the participants which have left, and of the events without a
+    /// participant.
+    pub(crate) stats: CachePadded<Counters>,
+
+    /// The latencies from deferring functions to executing them.
+    pub(crate) latency: CachePadded<AtomicHistogram>,
+
+    /// The function called when a participant is found stalled.
+    pub(crate) stall_handler: Mutex<Option<StallHandler>>,
+
+    /// The number of deferred functions in the global queue.
+    garbage: CachePadded<AtomicUsize>,
+
+    /// The number of background reclaimers, which take over the collection from participants.
+    reclaimers: AtomicUsize,
+
+    /// The number of requests of barriers for participants to hand their bags over.
+    flush_requests: CachePadded<AtomicUsize>,
+
+    /// The number of bags pushed back to the global queue by collections with a budget.
+    requeued: AtomicUsize,
 }
 
 impl Global {
@@ -181,18 +218,50 @@ impl Global {
 
     /// Creates a new global data for garbage collection.
     #[inline]
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(config: Config) -> Self {
         Self {
             locals: List::new(),
+            slots: EpochSlots::new(),
             queue: Queue::new(),
             epoch: CachePadded::new(AtomicEpoch::new(Epoch::starting())),
+            config: AtomicConfig::new(config),
+            stats: CachePadded::new(Counters::new()),
+            latency: CachePadded::new(AtomicHistogram::new()),
+            stall_handler: Mutex::new(None),
+            garbage: CachePadded::new(AtomicUsize::new(0)),
+            reclaimers: AtomicUsize::new(0),
+            flush_requests: CachePadded::new(AtomicUsize::new(0)),
+            requeued: AtomicUsize::new(0),
+        }
+    }
+
+    /// Records an event of the participant of `guard`.
+    #[inline]
+    pub(crate) fn record(&self, event: Event, n: u64, guard: &Guard) {
+        match unsafe { guard.local.as_ref() } {
+            Some(local) => local.stats().add(event, n),
+            None => self.stats.add_shared(event, n),
+        }
+    }
+
+    /// Sums up the statistics of the collector and all participants.
+    ///
+    /// The statistics of a participant reside in its epoch slot, so they are read without
+    /// traversing the list of participants.
+    pub(crate) fn stats(&self) -> Totals {
+        let mut totals = self.stats.load();
+        for slot in self.slots.iter() {
+            totals += slot.stats.load();
         }
+        totals
     }
 
     /// Pushes the bag into the global queue and replaces the bag with a new empty bag.
     pub(crate) fn push_bag(&self, bag: &mut Bag, guard: &Guard) {
-        GLOBAL_GARBAGE_COUNT.fetch_add(bag.0.len(), Ordering::AcqRel);
-        let bag = mem::replace(bag, Bag::new());
+        GLOBAL_GARBAGE_COUNT.fetch_add(bag.deferreds.len(), Ordering::AcqRel);
+        self.garbage
+            .fetch_add(bag.deferreds.len(), Ordering::Relaxed);
+        let bag = mem::replace(bag, Bag::with_capacity(self.config.bag_capacity()));
 
         atomic::fence(Ordering::SeqCst);
 
@@ -226,21 +295,147 @@ impl Global {
         }
         collecting.set(true);
 
-        for _ in 0..Self::COLLECTS_MIN_TRIALS {
+        // Without a budget, a collection pops a fixed number of bags. With one, the budget bounds
+        // the collection instead.
+        let mut budget = Budget::new(self.config.reclaim_budget.load());
+        let mut trials = if budget.is_limited() {
+            usize::MAX
+        } else {
+            Self::COLLECTS_MIN_TRIALS
+        };
+        while trials > 0 && !budget.is_exhausted() {
+            trials -= 1;
             match self.queue.try_pop_if(
                 &|sealed_bag: &SealedBag| sealed_bag.is_expired(global_epoch),
                 guard,
             ) {
                 None => break,
-                Some(sealed_bag) => {
-                    GLOBAL_GARBAGE_COUNT.fetch_sub(sealed_bag.bag.0.len(), Ordering::AcqRel);
-                    drop(sealed_bag);
+                Some(mut sealed_bag) => {
+                    let called = sealed_bag.bag.call_within(&mut budget);
+                    GLOBAL_GARBAGE_COUNT.fetch_sub(called, Ordering::AcqRel);
+                    self.garbage.fetch_sub(called, Ordering::Relaxed);
+                    // Every deferred function in the bag is accounted with the age of the bag,
+                    // which is an upper bound of its own latency.
+                    if let Some(since) = sealed_bag.bag.since {
+                        self.latency.record(since.elapsed(), called as u64);
+                    }
+                    if !sealed_bag.bag.is_empty() {
+                        // Leave the rest of the bag to a later collection. It is still expired.
+                        self.queue.push(sealed_bag, guard);
+                        self.requeued.fetch_add(1, Ordering::SeqCst);
+                    }
                 }
             }
         }
         collecting.set(false);
     }
 
+    /// Collects bags from the global queue as long as it makes progress.
+    pub(crate) fn collect_all(&self, guard: &Guard) {
+        loop {
+            let garbage = self.garbage();
+            self.collect(guard);
+            if self.garbage() == 0 || self.garbage() >= garbage {
+                return;
+            }
+        }
+    }
+
+    /// Waits until the global epoch advances twice, so that every participant pinned before the
+    /// call has been unpinned since.
+    ///
+    /// Returns `false` if `deadline` has passed before that. `local` must not be pinned, nor may
+    /// any other participant of the current thread.
+    pub(crate) fn synchronize(&self, local: &Local, deadline: Option<Instant>) -> bool {
+        let start = self.epoch.load(Ordering::SeqCst);
+        loop {
+            // Repin every time, so that `local` does not keep the epoch from advancing.
+            let epoch = self.try_advance(&local.pin());
+            if epoch.wrapping_sub(start) >= 2 {
+                return true;
+            }
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                return false;
+            }
+            thread::yield_now();
+        }
+    }
+
+    /// Waits until the deferred functions of all participants, deferred before the call, have
+    /// been executed.
+    ///
+    /// Every participant is requested to hand its bag over to the global queue the next time it
+    /// is pinned, so this waits for the other participants to be pinned again, and for the
+    /// participants pinned meanwhile to be unpinned.
+    ///
+    /// Returns `false` if `deadline` has passed before that. `local` must not be pinned, nor may
+    /// any other participant of the current thread.
+    pub(crate) fn barrier(&self, local: &Local, deadline: Option<Instant>) -> bool {
+        let expired = || deadline.is_some_and(|deadline| Instant::now() >= deadline);
+        let request = self
+            .flush_requests
+            .fetch_add(1, Ordering::SeqCst)
+            .wrapping_add(1);
+        loop {
+            // Pinning `local` hands its own bag over.
+            if self.flushed_since(request, &local.pin()) {
+                break;
+            }
+            if expired() {
+                return false;
+            }
+            thread::yield_now();
+        }
+
+        loop {
+            // The queue is FIFO, so the bags handed over so far have been popped once the marker
+            // is executed. A collection with a budget may push the rest of a bag back behind the
+            // marker, in which case a new marker is needed.
+            let requeued = self.requeued.load(Ordering::SeqCst);
+            let executed = Arc::new(AtomicBool::new(false));
+            {
+                let guard = local.pin();
+                let mut marker = Bag::with_capacity(1);
+                let executed = executed.clone();
+                let deferred = Deferred::new(move || executed.store(true, Ordering::Release));
+                unsafe { marker.try_push(deferred) }.unwrap_or_else(|_| unreachable!());
+                self.push_bag(&mut marker, &guard);
+            }
+            while !executed.load(Ordering::Acquire) {
+                self.collect_all(&local.pin());
+                if expired() {
+                    return false;
+                }
+                thread::yield_now();
+            }
+
+            // Other threads may still be executing the bags they popped before the marker.
+            if !self.synchronize(local, deadline) {
+                return false;
+            }
+            if self.requeued.load(Ordering::SeqCst) == requeued {
+                return true;
+            }
+        }
+    }
+
+    /// Returns `true` if every participant has handed its bag over since the `request`-th flush
+    /// request.
+    fn flushed_since(&self, request: usize, guard: &Guard) -> bool {
+        for local in self.locals.iter(guard) {
+            match local {
+                Err(IterError::Stalled) => return false,
+                Ok(local) => {
+                    let ack = local.flush_ack.load(Ordering::Acquire);
+                    if (ack.wrapping_sub(request) as isize) < 0 {
+                        return false;
+                    }
+                }
+            }
+        }
+        true
+    }
+
     /// Attempts to advance the global epoch.
     ///
     /// The global epoch can advance only if all currently pinned participants have been pinned in
@@ -254,46 +449,180 @@ impl Global {
         let global_epoch = self.epoch.load(Ordering::Relaxed);
         atomic::fence(Ordering::SeqCst);
 
-        // TODO(stjepang): `Local`s are stored in a linked list because linked lists are fairly
-        // easy to implement in a lock-free manner. However, traversal can be slow due to cache
-        // misses and data dependencies. We should experiment with other data structures as well.
+        let can_advance = match self.config.registry() {
+            Registry::List => self.check_locals(global_epoch, guard),
+            Registry::Array => self.check_slots(global_epoch),
+        };
+        if !can_advance {
+            self.record(Event::FailedAdvance, 1, guard);
+            return global_epoch;
+        }
+        atomic::fence(Ordering::Acquire);
+
+        // All pinned participants were pinned in the current global epoch.
+        // Now let's advance the global epoch...
+        //
+        // Note that if another thread already advanced it before us, this store will simply
+        // overwrite the global epoch with the same value. This is true because `try_advance` was
+        // called from a thread that was pinned in `global_epoch`, and the global epoch cannot be
+        // advanced two steps ahead of it.
+        let new_epoch = global_epoch.successor();
+        self.epoch.store(new_epoch, Ordering::Release);
+        self.record(Event::EpochAdvance, 1, guard);
+        notify!(on_epoch_advance(new_epoch));
+        new_epoch
+    }
+
+    /// Returns `true` if no participant in the list is pinned in an epoch other than
+    /// `global_epoch`.
+    fn check_locals(&self, global_epoch: Epoch, guard: &Guard) -> bool {
         for local in self.locals.iter(guard) {
             match local {
                 Err(IterError::Stalled) => {
                     // A concurrent thread stalled this iteration. That thread might also try to
                     // advance the epoch, in which case we leave the job to it. Otherwise, the
                     // epoch will not be advanced.
-                    return global_epoch;
+                    return false;
                 }
                 Ok(local) => {
-                    let local_epoch = local.epoch.load(Ordering::Relaxed);
+                    let local_epoch = local.slot().epoch.load(Ordering::Relaxed);
 
                     // If the participant was pinned in a different epoch, we cannot advance the
                     // global epoch just yet.
                     if local_epoch.is_pinned() && local_epoch.unpinned() != global_epoch {
-                        return global_epoch;
+                        self.observe_stall(local.slot(), local_epoch);
+                        return false;
                     }
                 }
             }
         }
-        atomic::fence(Ordering::Acquire);
+        true
+    }
 
-        // All pinned participants were pinned in the current global epoch.
-        // Now let's advance the global epoch...
-        //
-        // Note that if another thread already advanced it before us, this store will simply
-        // overwrite the global epoch with the same value. This is true because `try_advance` was
-        // called from a thread that was pinned in `global_epoch`, and the global epoch cannot be
-        // advanced two steps ahead of it.
-        let new_epoch = global_epoch.successor();
-        self.epoch.store(new_epoch, Ordering::Release);
-        new_epoch
+    /// Returns `true` if no slot of a participant is pinned in an epoch other than
+    /// `global_epoch`.
+    ///
+    /// Unlike the entries of the list, the slots are scanned without chasing pointers.
+    fn check_slots(&self, global_epoch: Epoch) -> bool {
+        for slot in self.slots.iter() {
+            let local_epoch = slot.epoch.load(Ordering::Relaxed);
+            if local_epoch.is_pinned() && local_epoch.unpinned() != global_epoch {
+                self.observe_stall(slot, local_epoch);
+                return false;
+            }
+        }
+        true
     }
 
     /// Checks if the global queue is empty.
     pub(crate) fn is_global_queue_empty(&self) -> bool {
         self.queue.is_empty()
     }
+
+    /// Returns the number of deferred functions in the global queue.
+    pub(crate) fn garbage(&self) -> usize {
+        self.garbage.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn attach_reclaimer(&self) {
+        self.reclaimers.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn detach_reclaimer(&self) {
+        self.reclaimers.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    /// Returns `true` if a background reclaimer collects the garbage of this collector.
+    #[inline]
+    pub(crate) fn has_reclaimer(&self) -> bool {
+        self.reclaimers.load(Ordering::Relaxed) > 0
+    }
+
+    /// Applies the backpressure if the global queue holds more deferred functions than the
+    /// high-water mark of the garbage limit.
+    #[inline]
+    fn check_garbage_limit(&self, guard: &Guard) {
+        if self.garbage() > self.config.garbage_limit.high_water() {
+            self.apply_backpressure(guard);
+        }
+    }
+
+    /// Collects garbage until the global queue falls to the low-water mark of the garbage limit,
+    /// or the backpressure gives up.
+    ///
+    /// The current participant stays pinned meanwhile, so only the bags sealed before it was
+    /// pinned can be collected.
+    #[cold]
+    fn apply_backpressure(&self, guard: &Guard) {
+        let limit = match self.config.garbage_limit.load() {
+            Some(limit) => limit,
+            None => return,
+        };
+        if unsafe { &*guard.local }.collecting.get() {
+            // The backpressure can not make progress in the middle of a collection.
+            return;
+        }
+
+        let deadline = Instant::now().checked_add(limit.backpressure().max_wait());
+        loop {
+            // Collect as long as it makes progress.
+            loop {
+                let garbage = self.garbage();
+                self.collect(guard);
+                if self.garbage() <= limit.low_water() {
+                    return;
+                }
+                if self.garbage() >= garbage {
+                    break;
+                }
+            }
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                return;
+            }
+            thread::yield_now();
+        }
+    }
+
+    /// Records that the participant of `slot`, pinned in `epoch`, keeps the global epoch from
+    /// advancing, and calls the stall handler if it has done so for too long.
+    ///
+    /// A participant lagging behind by an epoch is common under load, so this only updates the
+    /// atomics of the slot until the stall threshold is crossed.
+    fn observe_stall(&self, slot: &EpochSlot, epoch: Epoch) {
+        let threshold = self.config.stall_threshold();
+        if let Some(participant) = slot.stall.observe(epoch.unpinned(), threshold) {
+            let handler = self.stall_handler.lock().unwrap().clone();
+            if let Some(handler) = handler {
+                unwind::isolate(|| handler(&participant));
+            }
+        }
+    }
+
+    /// Returns the number of registered participants, i.e., the number of claimed epoch slots.
+    pub(crate) fn num_participants(&self) -> usize {
+        self.slots.num_claimed()
+    }
+
+    /// Returns the participants which have kept the global epoch from advancing for longer than
+    /// the stall threshold.
+    ///
+    /// As in `try_advance`, a participant pinned in the current global epoch does not block the
+    /// advancement, and is not reported. The epoch slots are scanned instead of the list of
+    /// participants, so the query does not need a participant of its own.
+    pub(crate) fn stalled_participants(&self) -> Vec<StalledParticipant> {
+        let threshold = self.config.stall_threshold();
+        let global_epoch = self.epoch.load(Ordering::Relaxed);
+        self.slots
+            .iter()
+            .filter_map(|slot| {
+                let epoch = slot.epoch.load(Ordering::Relaxed);
+                if !epoch.is_pinned() || epoch.unpinned() == global_epoch {
+                    return None;
+                }
+                slot.stall.check(epoch.unpinned(), threshold)
+            })
+            .collect()
+    }
 }
 
 /// Participant for garbage collection.
@@ -323,8 +652,11 @@ pub(crate) struct Local {
 
     collecting: Cell<bool>,
 
-    /// The local epoch.
-    epoch: CachePadded<AtomicEpoch>,
+    /// The slot of the local epoch, which resides in the `Global`.
+    slot: NonNull<EpochSlot>,
+
+    /// The last flush request of a barrier for which this participant handed its bag over.
+    flush_ack: AtomicUsize,
 }
 
 // Make sure `Local` is less than or equal to 2048 bytes.
@@ -340,16 +672,6 @@ fn local_size() {
 }
 
 impl Local {
-    #[inline]
-    fn counts_between_collect() -> usize {
-        unsafe { MAX_OBJECTS }
-    }
-
-    #[inline]
-    fn counts_between_try_advance() -> usize {
-        unsafe { MAX_OBJECTS * 2 }
-    }
-
     /// Registers a new `Local` in the provided `Global`.
     pub(crate) fn register(collector: &Collector) -> LocalHandle {
         unsafe {
@@ -358,7 +680,7 @@ impl Local {
             let local = Owned::new(Local {
                 entry: Entry::default(),
                 collector: UnsafeCell::new(ManuallyDrop::new(collector.clone())),
-                bag: UnsafeCell::new(Bag::new()),
+                bag: UnsafeCell::new(Bag::with_capacity(collector.global.config.bag_capacity())),
                 guard_count: Cell::new(0),
                 handle_count: Cell::new(1),
                 collect_count: Cell::new(0),
@@ -366,7 +688,9 @@ impl Local {
                 prev_epoch: Cell::new(Epoch::starting()),
                 manual_count: Cell::new(0),
                 collecting: Cell::new(false),
-                epoch: CachePadded::new(AtomicEpoch::new(Epoch::starting())),
+                slot: NonNull::from(Self::claim_slot(&collector.global)),
+                // The new bag is empty, so there is nothing to hand over.
+                flush_ack: AtomicUsize::new(collector.global.flush_requests.load(Ordering::SeqCst)),
             })
             .into_shared(unprotected());
             collector.global.locals.insert(local, unprotected());
@@ -378,7 +702,27 @@ impl Local {
 
     #[inline]
     pub(crate) fn epoch(&self) -> Epoch {
-        self.epoch.load(Ordering::Acquire)
+        self.slot().epoch.load(Ordering::Acquire)
+    }
+
+    /// Returns the slot of the local epoch.
+    #[inline]
+    fn slot(&self) -> &EpochSlot {
+        // The slots outlive the participants, which hold the collector until they leave.
+        unsafe { self.slot.as_ref() }
+    }
+
+    /// Claims an epoch slot for a participant registered by the current thread.
+    fn claim_slot(global: &Global) -> &EpochSlot {
+        let slot = global.slots.claim();
+        slot.stall.reset();
+        slot
+    }
+
+    /// Returns the statistics of this participant.
+    #[inline]
+    pub(crate) fn stats(&self) -> &Counters {
+        &self.slot().stats
     }
 
     /// Returns a reference to the `Global` in which this `Local` resides.
@@ -406,9 +750,15 @@ impl Local {
         let advance_count = self.advance_count.get().wrapping_add(1);
         self.advance_count.set(advance_count);
 
-        if advance_count % Self::counts_between_try_advance() == 0 {
+        if !is_collecting && self.global().has_reclaimer() {
+            // Leave the garbage to the background reclaimer.
+            return;
+        }
+
+        let config = &self.global().config;
+        if advance_count % config.advance_interval() == 0 {
             self.global().try_advance(&guard);
-        } else if is_collecting || collect_count % Self::counts_between_collect() == 0 {
+        } else if is_collecting || collect_count % config.collect_interval() == 0 {
             // After every `COUNTS_BETWEEN_COLLECT` try collecting some old garbage bags.
             self.global().collect(&guard);
         }
@@ -422,9 +772,14 @@ impl Local {
     pub(crate) unsafe fn defer(&self, mut deferred: Deferred, guard: &Guard) {
         let bag = self.bag.with_mut(|b| &mut *b);
 
+        let mut pushed = false;
         while let Err(d) = bag.try_push(deferred) {
             self.global().push_bag(bag, guard);
             deferred = d;
+            pushed = true;
+        }
+        if pushed {
+            self.global().check_garbage_limit(guard);
         }
 
         self.incr_counts(false, guard);
@@ -477,7 +832,7 @@ impl Local {
                     // works fine.  Using inline assembly would be a viable (and correct) alternative,
                     // but alas, that is not possible on stable Rust.
                     let current = Epoch::starting();
-                    let res = self.epoch.compare_exchange(
+                    let res = self.slot().epoch.compare_exchange(
                         current,
                         new_epoch,
                         Ordering::SeqCst,
@@ -489,14 +844,16 @@ impl Local {
                     // it should go a long way.
                     atomic::compiler_fence(Ordering::SeqCst);
                 } else {
-                    self.epoch.store(new_epoch, Ordering::Relaxed);
+                    self.slot().epoch.store(new_epoch, Ordering::Relaxed);
                     atomic::fence(Ordering::SeqCst);
                 }
 
                 if new_epoch.value() == self.global().epoch.load(Ordering::Acquire).value() {
                     break new_epoch;
                 }
-                self.epoch.store(Epoch::starting(), Ordering::Release);
+                self.slot()
+                    .epoch
+                    .store(Epoch::starting(), Ordering::Release);
             };
 
             // Reset the advance couter if epoch has advanced.
@@ -504,11 +861,23 @@ impl Local {
                 self.prev_epoch.set(new_epoch);
                 self.advance_count.set(0);
             }
+
+            let request = self.global().flush_requests.load(Ordering::Relaxed);
+            if request != self.flush_ack.load(Ordering::Relaxed) {
+                self.ack_flush_request(request, &guard);
+            }
         }
 
         guard
     }
 
+    /// Hands the bag over to the global queue, as requested by a barrier.
+    #[cold]
+    fn ack_flush_request(&self, request: usize, guard: &Guard) {
+        self.push_to_global(guard);
+        self.flush_ack.store(request, Ordering::Release);
+    }
+
     /// Unpins the `Local`.
     #[inline]
     pub(crate) fn unpin(&self) {
@@ -516,7 +885,9 @@ impl Local {
         self.guard_count.set(guard_count - 1);
 
         if guard_count == 1 {
-            self.epoch.store(Epoch::starting(), Ordering::Release);
+            self.slot()
+                .epoch
+                .store(Epoch::starting(), Ordering::Release);
 
             if self.handle_count.get() == 0 {
                 self.finalize();
@@ -531,14 +902,14 @@ impl Local {
 
         // Update the local epoch only if there's only one guard.
         if guard_count == 1 {
-            let epoch = self.epoch.load(Ordering::Relaxed);
+            let epoch = self.slot().epoch.load(Ordering::Relaxed);
             let global_epoch = self.global().epoch.load(Ordering::Relaxed).pinned();
 
             // Update the local epoch only if the global epoch is greater than the local epoch.
             if epoch != global_epoch {
                 // We store the new epoch with `Release` because we need to ensure any memory
                 // accesses from the previous epoch do not leak into the new one.
-                self.epoch.store(global_epoch, Ordering::Release);
+                self.slot().epoch.store(global_epoch, Ordering::Release);
 
                 // However, we don't need a following `SeqCst` fence, because it is safe for memory
                 // accesses from the new epoch to be executed before updating the local epoch. At
@@ -592,9 +963,17 @@ impl Local {
             // `Local` as deleted.
             let collector: Collector = ptr::read(self.collector.with(|c| &*(*c)));
 
+            // Hand the statistics over to the collector before leaving.
+            self.stats().drain_into(&collector.global.stats);
+
             // Mark this node in the linked list as deleted.
+            let slot = self.slot;
             self.entry.delete(unprotected());
 
+            // Let a new participant reuse the slot. It is released only after the node is marked
+            // as deleted, so that the list never has two live participants sharing a slot.
+            collector.global.slots.release(slot.as_ref());
+
             // Finally, drop the reference to the global. Note that this might be the last reference
             // to the `Global`. If so, the global data will be destroyed and all deferred functions
             // in its queue will be executed.
@@ -606,13 +985,13 @@ impl Local {
         let manual_count = self.manual_count.get().wrapping_add(1);
         self.manual_count.set(manual_count);
 
-        if manual_count % unsafe { MANUAL_EVENTS_BETWEEN_COLLECT } == 0 {
+        if manual_count % self.global().config.manual_collect_interval() == 0 {
             self.flush(guard);
         }
     }
 
     pub(crate) fn bag_len(&self) -> usize {
-        self.bag.with(|b| unsafe { &*b }.0.len())
+        self.bag.with(|b| unsafe { &*b }.deferreds.len())
     }
 }
 
@@ -660,10 +1039,11 @@ mod tests {
             FLAG.fetch_add(1, Ordering::Relaxed);
         }
 
-        let mut bag = Bag::new();
+        let capacity = Config::new().bag_capacity();
+        let mut bag = Bag::with_capacity(capacity);
         assert!(bag.is_empty());
 
-        for _ in 0..unsafe { MAX_OBJECTS } {
+        for _ in 0..capacity {
             assert!(unsafe { bag.try_push(Deferred::new(incr)).is_ok() });
             assert!(!bag.is_empty());
             assert_eq!(FLAG.load(Ordering::Relaxed), 0);
@@ -675,6 +1055,6 @@ mod tests {
         assert_eq!(FLAG.load(Ordering::Relaxed), 0);
 
         drop(bag);
-        assert_eq!(FLAG.load(Ordering::Relaxed), unsafe { MAX_OBJECTS });
+        assert_eq!(FLAG.load(Ordering::Relaxed), capacity);
     }
 }
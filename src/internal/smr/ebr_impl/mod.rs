@@ -50,10 +50,13 @@
 
 mod atomic;
 mod collector;
+mod config;
 mod deferred;
 mod epoch;
 mod guard;
 mod internal;
+mod registry;
+mod stall;
 mod sync;
 
 mod primitive {
@@ -95,14 +98,20 @@ mod primitive {
 
 pub use self::atomic::{Atomic, CompareExchangeError, Owned, Pointable, Pointer, Shared};
 pub use self::collector::{Collector, LocalHandle};
+pub use self::config::{Config, ConfigBuilder, Registry};
 pub use self::epoch::Epoch;
 pub use self::guard::{leaking, unprotected, Guard};
-pub use self::internal::{set_bag_capacity, set_manual_collection_interval};
+pub use self::stall::{log_stall, StalledParticipant};
 
 #[allow(deprecated)]
 pub use self::atomic::{CompareAndSetError, CompareAndSetOrdering};
 
 mod default;
-pub use self::default::{default_collector, is_pinned, pin};
+pub(crate) use self::default::record;
+pub use self::default::{
+    barrier, default_collector, is_pinned, pin, synchronize, thread_stats, try_barrier, Participant,
+};
+#[allow(deprecated)]
+pub use self::default::{set_bag_capacity, set_manual_collection_interval};
 
 pub use self::internal::GLOBAL_GARBAGE_COUNT;
@@ -0,0 +1,216 @@
+//! The epochs of participants in a growable array.
+//!
+//! Every participant claims a slot of the array when it registers, and publishes its local epoch
+//! in the slot. Unlike the entries of the list of participants, the slots can be scanned without
+//! chasing pointers, which makes attempts to advance the global epoch cheaper with many
+//! participants. See [`Registry`](super::Registry).
+//!
+//! The array consists of segments of doubling sizes, which are never moved or freed until the
+//! collector is destroyed, so a participant may keep a reference to its slot. A slot released by
+//! a leaving participant is reused by a new one.
+
+use core::ptr;
+use core::sync::atomic::{AtomicPtr, AtomicU8, AtomicUsize, Ordering};
+
+use crossbeam_utils::CachePadded;
+
+use super::epoch::{AtomicEpoch, Epoch};
+use super::stall::Stall;
+use crate::internal::stats::Counters;
+
+/// The number of slots of the first segment.
+const FIRST_SEGMENT: usize = 8;
+
+/// The number of segments, which hold `FIRST_SEGMENT * (2^SEGMENTS - 1)` slots altogether.
+const SEGMENTS: usize = 32;
+
+/// The states of a slot.
+const FRESH: u8 = 0;
+const CLAIMED: u8 = 1;
+const RELEASED: u8 = 2;
+
+/// The slot of the local epoch of a participant.
+#[derive(Debug)]
+pub(crate) struct EpochSlot {
+    pub(crate) epoch: AtomicEpoch,
+    /// The statistics of the participant, which are handed over to the collector when it leaves.
+    pub(crate) stats: Counters,
+    /// How long the participant has kept the global epoch from advancing.
+    pub(crate) stall: Stall,
+    /// Whether the slot is fresh, claimed, or released. Only a released slot is reused, because a
+    /// fresh one is claimed by the participant which has grown the array to it.
+    state: AtomicU8,
+}
+
+impl EpochSlot {
+    fn new() -> Self {
+        Self {
+            epoch: AtomicEpoch::new(Epoch::starting()),
+            stats: Counters::new(),
+            stall: Stall::new(),
+            state: AtomicU8::new(FRESH),
+        }
+    }
+}
+
+/// A growable array of [`EpochSlot`]s.
+#[derive(Debug)]
+pub(crate) struct EpochSlots {
+    segments: [AtomicPtr<CachePadded<EpochSlot>>; SEGMENTS],
+    /// The number of slots which have ever been claimed.
+    len: AtomicUsize,
+}
+
+impl EpochSlots {
+    pub(crate) fn new() -> Self {
+        Self {
+            segments: Default::default(),
+            len: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the number of slots of the `index`-th segment.
+    fn segment_len(index: usize) -> usize {
+        FIRST_SEGMENT << index
+    }
+
+    /// Returns the segment and the offset in it of the `index`-th slot.
+    fn locate(index: usize) -> (usize, usize) {
+        let segment = (index / FIRST_SEGMENT + 1).ilog2() as usize;
+        (segment, index - FIRST_SEGMENT * ((1 << segment) - 1))
+    }
+
+    /// Claims a released slot, or a new one.
+    pub(crate) fn claim(&self) -> &EpochSlot {
+        self.iter()
+            .find(|slot| {
+                slot.state.load(Ordering::Relaxed) == RELEASED
+                    && slot
+                        .state
+                        .compare_exchange(RELEASED, CLAIMED, Ordering::Acquire, Ordering::Relaxed)
+                        .is_ok()
+            })
+            .unwrap_or_else(|| self.claim_new())
+    }
+
+    #[cold]
+    fn claim_new(&self) -> &EpochSlot {
+        let index = self.len.fetch_add(1, Ordering::Relaxed);
+        let (segment, offset) = Self::locate(index);
+        assert!(segment < SEGMENTS, "too many participants");
+
+        let slots = self.segment(segment).unwrap_or_else(|| self.grow(segment));
+        let slot = &slots[offset];
+        slot.state.store(CLAIMED, Ordering::Relaxed);
+        slot
+    }
+
+    /// Allocates the `index`-th segment, unless another thread has done so.
+    fn grow(&self, index: usize) -> &[CachePadded<EpochSlot>] {
+        let len = Self::segment_len(index);
+        let new: Box<[_]> = (0..len)
+            .map(|_| CachePadded::new(EpochSlot::new()))
+            .collect();
+        let new = Box::into_raw(new).cast::<CachePadded<EpochSlot>>();
+        if let Err(current) = self.segments[index].compare_exchange(
+            ptr::null_mut(),
+            new,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        ) {
+            drop(unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(new, len)) });
+            return unsafe { &*ptr::slice_from_raw_parts(current, len) };
+        }
+        unsafe { &*ptr::slice_from_raw_parts(new, len) }
+    }
+
+    fn segment(&self, index: usize) -> Option<&[CachePadded<EpochSlot>]> {
+        let segment = self.segments[index].load(Ordering::Acquire);
+        (!segment.is_null())
+            .then(|| unsafe { &*ptr::slice_from_raw_parts(segment, Self::segment_len(index)) })
+    }
+
+    /// Releases a slot, which must be unpinned, for another participant.
+    pub(crate) fn release(&self, slot: &EpochSlot) {
+        debug_assert!(!slot.epoch.load(Ordering::Relaxed).is_pinned());
+        slot.state.store(RELEASED, Ordering::Release);
+    }
+
+    /// Returns the number of the slots which are claimed by participants.
+    pub(crate) fn num_claimed(&self) -> usize {
+        self.iter()
+            .filter(|slot| slot.state.load(Ordering::Relaxed) == CLAIMED)
+            .count()
+    }
+
+    /// Returns an iterator over the slots which have ever been claimed.
+    ///
+    /// A slot which is being claimed for the first time may be skipped.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &EpochSlot> {
+        let len = self.len.load(Ordering::Acquire);
+        let mut start = 0;
+        (0..SEGMENTS)
+            .map_while(move |index| {
+                let segment_start = start;
+                start += Self::segment_len(index);
+                (segment_start < len).then(|| {
+                    let slots = self.segment(index).unwrap_or_default();
+                    &slots[..slots.len().min(len - segment_start)]
+                })
+            })
+            .flatten()
+            .map(|slot| &**slot)
+    }
+}
+
+impl Drop for EpochSlots {
+    fn drop(&mut self) {
+        for (index, segment) in self.segments.iter_mut().enumerate() {
+            let segment = *segment.get_mut();
+            if !segment.is_null() {
+                let len = Self::segment_len(index);
+                drop(unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(segment, len)) });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locate() {
+        assert_eq!(EpochSlots::locate(0), (0, 0));
+        assert_eq!(EpochSlots::locate(7), (0, 7));
+        assert_eq!(EpochSlots::locate(8), (1, 0));
+        assert_eq!(EpochSlots::locate(23), (1, 15));
+        assert_eq!(EpochSlots::locate(24), (2, 0));
+    }
+
+    #[test]
+    fn claim_and_release() {
+        let slots = EpochSlots::new();
+        let claimed: Vec<_> = (0..100).map(|_| slots.claim()).collect();
+        assert_eq!(slots.iter().count(), 100);
+        for (i, slot) in claimed.iter().enumerate() {
+            slot.epoch
+                .store(Epoch::starting().successor(), Ordering::Relaxed);
+            assert!(ptr::eq(*slot, slots.iter().nth(i).unwrap()));
+        }
+
+        // Released slots are reused before the array grows.
+        for slot in &claimed[10..20] {
+            slot.epoch.store(Epoch::starting(), Ordering::Relaxed);
+            slots.release(slot);
+        }
+        for _ in 0..10 {
+            let slot = slots.claim();
+            assert!(claimed[10..20]
+                .iter()
+                .any(|claimed| ptr::eq(*claimed, slot)));
+        }
+        slots.claim();
+        assert_eq!(slots.iter().count(), 101);
+    }
+}
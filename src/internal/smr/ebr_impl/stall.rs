@@ -0,0 +1,191 @@
+//! Detection of participants which keep the global epoch from advancing.
+//!
+//! A participant pinned in an old epoch blocks `Global::try_advance`, which then observes the
+//! participant while scanning the participants. On the first observation in an epoch, the time is
+//! recorded in the atomics of the `Stall` of the participant's epoch slot. Once the participant has
+//! blocked the advancement for longer than the collector's stall threshold, it is reported to the
+//! stall handler, once per epoch.
+//!
+//! The tracker resides in the epoch slot rather than in the participant, so that the stalled
+//! participants can be queried by scanning the slots, without registering a participant to
+//! traverse the list.
+
+use core::fmt;
+use core::sync::atomic::{AtomicU64, Ordering};
+use core::time::Duration;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, Thread, ThreadId};
+use std::time::Instant;
+
+use super::epoch::{AtomicEpoch, Epoch};
+
+/// A participant which has been pinned in an old epoch for a long time.
+#[derive(Debug, Clone)]
+pub struct StalledParticipant {
+    /// The name of the thread which registered the participant.
+    pub thread_name: Option<String>,
+    /// The id of the thread which registered the participant.
+    pub thread_id: ThreadId,
+    /// The epoch in which the participant is pinned.
+    pub epoch: Epoch,
+    /// How long the participant has been observed to block the advancement of the global epoch.
+    pub duration: Duration,
+}
+
+impl fmt::Display for StalledParticipant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "thread {:?} ({}) has been pinned in epoch {} for {:?}",
+            self.thread_id,
+            self.thread_name.as_deref().unwrap_or("<unnamed>"),
+            self.epoch.value(),
+            self.duration
+        )
+    }
+}
+
+/// A stall handler which prints the stalled participant to the standard error.
+pub fn log_stall(participant: &StalledParticipant) {
+    eprintln!("cdrc-rs: {}", participant);
+}
+
+/// A function called when a participant is found stalled.
+pub(crate) type StallHandler = Arc<dyn Fn(&StalledParticipant) + Send + Sync>;
+
+/// The value of `Stall::since` while a thread records a new stall.
+const RECORDING: u64 = u64::MAX;
+
+/// The bit of `Stall::since` which is set once the stall has been reported.
+const REPORTED: u64 = 1;
+
+/// The stall tracker of an epoch slot.
+///
+/// The state of the stall is kept in atomics, so that observing a participant which lags behind,
+/// the common case under load, neither locks nor touches the thread. The thread is read only to
+/// build a report, once the participant has blocked for the threshold.
+pub(crate) struct Stall {
+    /// The epoch in which the participant was first observed blocking the advancement.
+    epoch: AtomicEpoch,
+    /// The time of the first observation in `epoch`, in nanoseconds since `origin`, shifted left by
+    /// one bit. The lowest bit is `REPORTED`. `RECORDING` while a new stall is being recorded.
+    since: AtomicU64,
+    /// The fixed instant the times are measured from.
+    origin: Instant,
+    /// The thread which registered the participant.
+    thread: Mutex<Option<Thread>>,
+}
+
+impl Stall {
+    /// Creates a tracker which tracks no participant yet.
+    pub(crate) fn new() -> Self {
+        Self {
+            // The tracked epochs are unpinned, so a pinned one matches none of them.
+            epoch: AtomicEpoch::new(Epoch::starting().pinned()),
+            since: AtomicU64::new(0),
+            origin: Instant::now(),
+            thread: Mutex::new(None),
+        }
+    }
+
+    /// Starts tracking a participant registered by the current thread, forgetting the stall of
+    /// the previous owner of the slot.
+    pub(crate) fn reset(&self) {
+        *self.thread.lock().unwrap() = Some(thread::current());
+        self.epoch
+            .store(Epoch::starting().pinned(), Ordering::Release);
+    }
+
+    /// Returns the time elapsed since `origin`, in nanoseconds.
+    fn now(&self) -> u64 {
+        self.origin.elapsed().as_nanos() as u64
+    }
+
+    /// Loads the time of the first observation in `epoch`, shifted as in `since`.
+    ///
+    /// Returns `Err` with the observed value of `since` if the stall is not of `epoch`, and `None`
+    /// if a new stall is being recorded.
+    fn load(&self, epoch: Epoch) -> Option<Result<u64, u64>> {
+        loop {
+            let since = self.since.load(Ordering::Acquire);
+            if since == RECORDING {
+                return None;
+            }
+            if self.epoch.load(Ordering::Acquire) != epoch {
+                return Some(Err(since));
+            }
+            // Make sure that `since` was not replaced while `epoch` was read.
+            if self.since.load(Ordering::Relaxed) == since {
+                return Some(Ok(since));
+            }
+        }
+    }
+
+    /// Builds the report of the participant, which has been pinned in `epoch` since `since`.
+    fn participant(&self, epoch: Epoch, since: u64, now: u64) -> Option<StalledParticipant> {
+        let thread = self.thread.lock().unwrap();
+        let thread = thread.as_ref()?;
+        Some(StalledParticipant {
+            thread_name: thread.name().map(String::from),
+            thread_id: thread.id(),
+            epoch,
+            duration: Duration::from_nanos(now.saturating_sub(since >> 1)),
+        })
+    }
+
+    /// Records that the participant blocks the advancement while pinned in `epoch`.
+    ///
+    /// Returns the participant if it has blocked for `threshold` and has not been reported yet.
+    pub(crate) fn observe(&self, epoch: Epoch, threshold: Duration) -> Option<StalledParticipant> {
+        match self.load(epoch)? {
+            Ok(since) => {
+                if since & REPORTED != 0 {
+                    return None;
+                }
+                let now = self.now();
+                if now.saturating_sub(since >> 1) < threshold.as_nanos() as u64 {
+                    return None;
+                }
+                // Only the thread which marks the stall as reported builds the report.
+                self.since
+                    .compare_exchange(
+                        since,
+                        since | REPORTED,
+                        Ordering::Relaxed,
+                        Ordering::Relaxed,
+                    )
+                    .ok()?;
+                self.participant(epoch, since, now)
+            }
+            Err(since) => {
+                // The first observation in `epoch`. Only one thread records the new stall.
+                if self
+                    .since
+                    .compare_exchange(since, RECORDING, Ordering::Acquire, Ordering::Relaxed)
+                    .is_ok()
+                {
+                    self.epoch.store(epoch, Ordering::Release);
+                    self.since.store(self.now() << 1, Ordering::Release);
+                }
+                None
+            }
+        }
+    }
+
+    /// Returns the participant if it is still pinned in `epoch`, and has blocked the advancement
+    /// for `threshold` in it.
+    pub(crate) fn check(&self, epoch: Epoch, threshold: Duration) -> Option<StalledParticipant> {
+        let since = self.load(epoch)?.ok()?;
+        let now = self.now();
+        if now.saturating_sub(since >> 1) < threshold.as_nanos() as u64 {
+            return None;
+        }
+        self.participant(epoch, since, now)
+    }
+}
+
+impl fmt::Debug for Stall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Stall { .. }")
+    }
+}
@@ -1,25 +1,33 @@
-use std::{mem::swap, ptr::null};
+use std::{
+    mem::{self, swap},
+    ptr,
+    time::{Duration, Instant},
+};
 
 use atomic::Ordering;
 
-use crate::{Acquired, Counted, Cs, TaggedCnt};
+use crate::internal::leak;
+use crate::internal::observer::notify;
+use crate::internal::pool;
+use crate::internal::stats::Event;
+use crate::{Acquired, CountedObject, Cs, EjectAction, RetireType, Tagged};
 
-use super::hp_impl::{HazardPointer, Thread, DEFAULT_THREAD};
+use super::hp_impl::{self, HazardPointer, ThreadPtr};
 
 pub struct AcquiredHP<T> {
     hazptr: HazardPointer,
-    ptr: TaggedCnt<T>,
+    ptr: Tagged<T>,
 }
 
 impl<T> Acquired<T> for AcquiredHP<T> {
     #[inline]
     fn clear(&mut self) {
         self.hazptr.reset_protection();
-        self.ptr = TaggedCnt::null();
+        self.ptr = Tagged::null();
     }
 
     #[inline]
-    fn as_ptr(&self) -> TaggedCnt<T> {
+    fn as_ptr(&self) -> Tagged<T> {
         self.ptr
     }
 
@@ -32,7 +40,7 @@ impl<T> Acquired<T> for AcquiredHP<T> {
     fn null() -> Self {
         Self {
             hazptr: HazardPointer::default(),
-            ptr: TaggedCnt::null(),
+            ptr: Tagged::null(),
         }
     }
 
@@ -61,7 +69,16 @@ impl<T> Acquired<T> for AcquiredHP<T> {
 }
 
 pub struct CsHP {
-    thread: *const Thread,
+    thread: ThreadPtr,
+}
+
+impl From<&hp_impl::Participant> for CsHP {
+    #[inline]
+    fn from(participant: &hp_impl::Participant) -> Self {
+        Self {
+            thread: participant.thread(),
+        }
+    }
 }
 
 impl Cs for CsHP {
@@ -69,8 +86,11 @@ impl Cs for CsHP {
 
     #[inline]
     fn new() -> Self {
-        let thread = DEFAULT_THREAD.with(|t| (&**t) as *const Thread);
-        Self { thread }
+        // The thread-local thread may have been destroyed, e.g., if an `Rc` is dropped in the
+        // destructor of another thread-local.
+        Self {
+            thread: ThreadPtr::current(),
+        }
     }
 
     #[inline]
@@ -80,27 +100,31 @@ impl Cs for CsHP {
 
     #[inline]
     unsafe fn unprotected() -> Self {
-        Self { thread: null() }
+        Self {
+            thread: ThreadPtr::null(),
+        }
     }
 
     #[inline]
-    fn create_object<T>(obj: T) -> *mut crate::Counted<T> {
-        let obj = Counted::new(obj);
-        Box::into_raw(Box::new(obj))
+    fn create_object<O: CountedObject + Send>(obj: O::Target) -> *mut O {
+        hp_impl::record(Event::Allocated, 1);
+        let ptr = pool::alloc(O::new(obj));
+        leak::track::<Self, O>(ptr);
+        ptr
     }
 
     #[inline]
-    fn reserve<T>(&self, ptr: TaggedCnt<T>, shield: &mut Self::RawShield<T>) {
+    fn reserve<O>(&self, ptr: Tagged<O>, shield: &mut Self::RawShield<O>) {
         shield.ptr = ptr;
         shield.hazptr.protect_raw(ptr.as_raw());
         membarrier::light_membarrier();
     }
 
     #[inline]
-    fn protect_snapshot<T>(
+    fn protect_snapshot<O: CountedObject>(
         &self,
-        link: &atomic::Atomic<TaggedCnt<T>>,
-        shield: &mut Self::RawShield<T>,
+        link: &atomic::Atomic<Tagged<O>>,
+        shield: &mut Self::RawShield<O>,
     ) -> bool {
         let mut ptr = link.load(Ordering::Relaxed);
         loop {
@@ -124,24 +148,67 @@ impl Cs for CsHP {
     }
 
     #[inline]
-    unsafe fn own_object<T>(ptr: *mut Counted<T>) -> Counted<T> {
-        *Box::from_raw(ptr)
+    unsafe fn own_object<O: CountedObject>(ptr: *mut O) -> O {
+        hp_impl::record(Event::Destroyed, 1);
+        leak::untrack(ptr);
+        if !pool::is_enabled() {
+            return *Box::from_raw(ptr);
+        }
+
+        let obj = ptr::read(ptr);
+        // A hazard pointer may still point to this block. Recycle it only after a hazard scan
+        // has confirmed that it is not protected.
+        let thread = ThreadPtr::current();
+        (*thread.as_ptr()).defer(ptr, move || unsafe { pool::free(ptr) });
+        obj
     }
 
     #[inline]
-    unsafe fn retire<T>(&self, ptr: *mut Counted<T>, ret_type: crate::RetireType) {
+    unsafe fn retire<O: CountedObject + Send>(&self, ptr: *mut O, ret_type: RetireType) {
         debug_assert!(!ptr.is_null());
+        notify!(on_retire(ptr as *const u8, ret_type));
         let cnt = &mut *ptr;
-        if let Some(thread) = self.thread.as_ref() {
+        if let Some(thread) = self.thread.as_ptr().as_ref() {
+            let size = mem::size_of::<O>() as u64;
+            thread.record(Event::Retired, 1);
+            thread.record(Event::RetiredBytes, size);
             thread.defer(ptr, move || {
                 let inner_guard = Self::new();
                 inner_guard.eject(cnt, ret_type);
+                hp_impl::record(Event::Reclaimed, 1);
+                hp_impl::record(Event::ReclaimedBytes, size);
             });
         } else {
             self.eject(cnt, ret_type);
         }
     }
 
+    #[inline]
+    unsafe fn decrement_ref_cnt<O: CountedObject + Send>(&self, cnt: &mut O) {
+        debug_assert!(cnt.ref_count() >= 1);
+        match cnt.release_ref() {
+            EjectAction::Nothing => {}
+            EjectAction::Delay => self.retire(cnt, RetireType::Dispose),
+            EjectAction::Destroy => {
+                // `release_ref` has already disposed the object.
+                hp_impl::record(Event::Disposed, 1);
+                notify!(on_dispose(cnt as *mut O as *const u8));
+                self.destroy(cnt);
+            }
+        }
+    }
+
+    #[inline]
+    unsafe fn dispose<O: CountedObject>(&self, cnt: &mut O) {
+        debug_assert!(cnt.ref_count() == 0);
+        hp_impl::record(Event::Disposed, 1);
+        cnt.dispose();
+        notify!(on_dispose(cnt as *mut O as *const u8));
+        if cnt.release_weak() {
+            self.destroy(cnt);
+        }
+    }
+
     #[inline]
     fn clear(&mut self) {
         // No-op for HP.
@@ -149,8 +216,43 @@ impl Cs for CsHP {
 
     #[inline]
     fn eager_reclaim(&mut self) {
-        if let Some(thread) = unsafe { self.thread.as_ref() } {
+        if let Some(thread) = unsafe { self.thread.as_ptr().as_ref() } {
             thread.eager_reclaim();
         }
     }
+
+    fn synchronize() {
+        let thread = ThreadPtr::current();
+        hp_impl::DEFAULT_DOMAIN.synchronize(unsafe { &*thread.as_ptr() });
+    }
+
+    fn barrier() {
+        // Ejecting an object may retire the objects it points to, so repeat until a round retires
+        // nothing more.
+        loop {
+            let retired = hp_impl::thread_stats().retired;
+            let thread = ThreadPtr::current();
+            hp_impl::DEFAULT_DOMAIN.barrier(unsafe { &*thread.as_ptr() });
+            if hp_impl::thread_stats().retired == retired {
+                return;
+            }
+        }
+    }
+
+    fn try_barrier(timeout: Duration) -> bool {
+        let deadline = Instant::now().checked_add(timeout);
+        loop {
+            let retired = hp_impl::thread_stats().retired;
+            let remaining = deadline.map_or(Duration::MAX, |deadline| {
+                deadline.saturating_duration_since(Instant::now())
+            });
+            let thread = ThreadPtr::current();
+            if !hp_impl::DEFAULT_DOMAIN.try_barrier(unsafe { &*thread.as_ptr() }, remaining) {
+                return false;
+            }
+            if hp_impl::thread_stats().retired == retired {
+                return true;
+            }
+        }
+    }
 }
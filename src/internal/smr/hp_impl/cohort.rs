@@ -0,0 +1,223 @@
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::retire::{Retired, RetiredList};
+use super::scan::HazardSet;
+use super::thread::{Thread, ThreadPtr};
+use crate::internal::histogram::LatencyHistogram;
+use crate::internal::observer::notify;
+use crate::internal::stats::Event;
+use crate::internal::unwind;
+
+/// A group of retired objects which belong to one container, e.g., the nodes of a list.
+///
+/// While a thread runs a closure with [`Cohort::scope`], the objects it retires to the default
+/// domain, e.g., by dropping `Rc`s with `CsHP`, are retired to the cohort instead, and so are the
+/// objects retired while they are reclaimed. The thread buffers the objects, and pushes them to
+/// the cohort in a batch every `collect_interval` retirements, reclaiming the unprotected objects
+/// of the cohort, and when it leaves the scope.
+///
+/// Dropping the cohort reclaims all of its unprotected objects. Thus a container dropped along with
+/// its cohort does not leave its objects to unrelated reclamations. Barriers do not wait for the
+/// objects of a cohort.
+///
+/// The objects still protected once a reclamation reclaims nothing, e.g., because of a live
+/// `Snapshot` of the dropping thread, are handed over to the domain, which reclaims them as it
+/// does the other retired objects. A cohort created with [`Cohort::with_drop_timeout`] instead
+/// keeps reclaiming them until the hazard pointers are reset, for up to the timeout.
+#[derive(Debug)]
+pub struct Cohort {
+    retireds: RetiredList,
+    /// The number of objects in `retireds`, including the ones held by a reclamation.
+    len: AtomicUsize,
+    /// Whether a thread is reclaiming the objects.
+    reclaiming: AtomicBool,
+    /// The hazard pointers found by the last scan, whose buffers are reused by the next one. Only
+    /// the thread which has set `reclaiming` accesses it.
+    scanned: UnsafeCell<HazardSet>,
+    /// How long a drop waits for the protected objects before handing them over to the domain.
+    drop_timeout: Duration,
+}
+
+unsafe impl Send for Cohort {}
+unsafe impl Sync for Cohort {}
+
+impl Cohort {
+    pub const fn new() -> Self {
+        Self::with_drop_timeout(Duration::ZERO)
+    }
+
+    /// Creates a cohort whose drop waits for up to `timeout` for the hazard pointers protecting its
+    /// objects to be reset, before handing the protected objects over to the domain.
+    pub const fn with_drop_timeout(timeout: Duration) -> Self {
+        Self {
+            retireds: RetiredList::new(),
+            len: AtomicUsize::new(0),
+            reclaiming: AtomicBool::new(false),
+            scanned: UnsafeCell::new(HazardSet::new()),
+            drop_timeout: timeout,
+        }
+    }
+
+    /// Runs `f`, retiring the objects of the current thread to this cohort meanwhile.
+    ///
+    /// The cohort which the thread has been in before, if any, is restored when `f` returns or
+    /// panics.
+    pub fn scope<R>(&self, f: impl FnOnce() -> R) -> R {
+        let thread = ThreadPtr::current();
+        let _restore = RestoreOnDrop {
+            prev: enter(&thread, self),
+            thread: &thread,
+        };
+        f()
+    }
+
+    /// Returns the number of retired objects which have not been reclaimed yet.
+    ///
+    /// The objects buffered by a thread in [`scope`](Cohort::scope) are counted once they are
+    /// pushed to the cohort.
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Acquire)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub(crate) unsafe fn defer<F: FnOnce()>(&self, thread: &Thread, ptr: *mut u8, f: F) {
+        let mut buffer = thread.cohort_retired.borrow_mut();
+        buffer.push(Retired::new(ptr, f));
+        if buffer.len() >= thread.domain().config.collect_interval() {
+            drop(buffer);
+            self.flush(thread);
+            self.reclaim(thread);
+        }
+    }
+
+    /// Pushes the objects buffered by `thread` to the cohort.
+    fn flush(&self, thread: &Thread) {
+        let retireds = thread.cohort_retired.take();
+        if !retireds.is_empty() {
+            self.len.fetch_add(retireds.len(), Ordering::Relaxed);
+            self.retireds.push(retireds);
+        }
+    }
+
+    /// Reclaims the unprotected objects, unless another reclamation of the cohort is in progress.
+    ///
+    /// Returns the number of reclaimed objects.
+    fn reclaim(&self, thread: &Thread) -> usize {
+        if self.reclaiming.swap(true, Ordering::Acquire) {
+            return 0;
+        }
+
+        let retireds = self.retireds.pop_all();
+        let reclaimed = if retireds.is_empty() {
+            0
+        } else {
+            self.reclaim_popped(thread, retireds)
+        };
+        self.reclaiming.store(false, Ordering::Release);
+        reclaimed
+    }
+
+    fn reclaim_popped(&self, thread: &Thread, retireds: Vec<Retired>) -> usize {
+        let retireds_len = retireds.len();
+        membarrier::heavy();
+
+        // The thread may be in the middle of its own reclamation, so the buffers of the cohort are
+        // reused instead of the thread's.
+        let guarded_ptrs = unsafe { &mut *self.scanned.get() };
+        guarded_ptrs.collect(thread.domain(), thread);
+        thread.record(Event::Scan, 1);
+        thread.record(Event::HazardFound, guarded_ptrs.len() as u64);
+
+        // The objects retired by the deferred functions belong to the cohort as well.
+        let prev = enter(thread, self);
+        let now = Instant::now();
+        let mut latency = LatencyHistogram::default();
+        let not_freed: Vec<Retired> = retireds
+            .into_iter()
+            .filter(|element| {
+                if guarded_ptrs.contains(element.ptr) {
+                    return true;
+                }
+                latency.record(now.saturating_duration_since(element.retired_at), 1);
+                unwind::isolate(|| unsafe { element.call() });
+                false
+            })
+            .collect();
+        enter(thread, prev);
+
+        thread.domain().latency.merge(&latency);
+        let remaining = not_freed.len();
+        if remaining > 0 {
+            self.retireds.push(not_freed);
+        }
+        self.len
+            .fetch_sub(retireds_len - remaining, Ordering::AcqRel);
+        notify!(on_reclamation(retireds_len - remaining, remaining));
+        retireds_len - remaining
+    }
+}
+
+impl Default for Cohort {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Cohort {
+    /// Reclaims the objects until a reclamation reclaims nothing, and the drop timeout has passed.
+    /// The remaining objects are handed over to the domain.
+    fn drop(&mut self) {
+        let reclaimer = ThreadPtr::current();
+        let reclaimer = unsafe { &*reclaimer.as_ptr() };
+        let start = Instant::now();
+        loop {
+            // Reclaiming an object may retire the objects it points to, e.g., the next node of a
+            // list, so the reclamations go on while they make progress.
+            let reclaimed = self.reclaim(reclaimer);
+            if self.is_empty() {
+                return;
+            }
+            if reclaimed == 0 {
+                if start.elapsed() >= self.drop_timeout {
+                    break;
+                }
+                thread::yield_now();
+            }
+        }
+
+        let retireds = self.retireds.pop_all();
+        let domain = reclaimer.domain();
+        domain
+            .num_garbages
+            .fetch_add(retireds.len(), Ordering::AcqRel);
+        self.len.fetch_sub(retireds.len(), Ordering::AcqRel);
+        domain.retireds.push(retireds);
+    }
+}
+
+/// Makes `thread` retire to `cohort`, which may be null, and returns the cohort it has retired to
+/// before, after pushing the objects buffered for the latter to it.
+fn enter(thread: &Thread, cohort: *const Cohort) -> *const Cohort {
+    if let Some(prev) = unsafe { thread.cohort.get().as_ref() } {
+        prev.flush(thread);
+    }
+    thread.cohort.replace(cohort)
+}
+
+/// Restores the cohort of a thread when dropped.
+struct RestoreOnDrop<'a> {
+    thread: &'a Thread,
+    prev: *const Cohort,
+}
+
+impl Drop for RestoreOnDrop<'_> {
+    fn drop(&mut self) {
+        enter(self.thread, self.prev);
+    }
+}
@@ -0,0 +1,189 @@
+//! Tuning parameters of a domain.
+
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use crate::internal::config::{
+    check_positive, AtomicGarbageLimit, AtomicReclaimBudget, ConfigError, GarbageLimit,
+    ReclaimBudget,
+};
+
+/// The tuning parameters of a [`Domain`](super::Domain).
+///
+/// A `Config` is created by a [`ConfigBuilder`], which validates the parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Config {
+    flush_interval: usize,
+    collect_interval: usize,
+    garbage_limit: Option<GarbageLimit>,
+    reclaim_budget: Option<ReclaimBudget>,
+    local_reclamation: bool,
+}
+
+impl Config {
+    /// Returns the default configuration.
+    pub const fn new() -> Self {
+        Self {
+            flush_interval: 64,
+            collect_interval: 128,
+            garbage_limit: None,
+            reclaim_budget: None,
+            local_reclamation: false,
+        }
+    }
+
+    /// Returns a builder initialized with the default configuration.
+    pub fn builder() -> ConfigBuilder {
+        Self::new().into_builder()
+    }
+
+    /// Returns a builder initialized with this configuration.
+    pub fn into_builder(self) -> ConfigBuilder {
+        ConfigBuilder { config: self }
+    }
+
+    /// The number of retirements of a thread between flushes of its retired objects to the domain.
+    pub fn flush_interval(&self) -> usize {
+        self.flush_interval
+    }
+
+    /// The number of retirements of a thread between hazard scans.
+    pub fn collect_interval(&self) -> usize {
+        self.collect_interval
+    }
+
+    /// The limit on the number of retired objects flushed to the domain, if any.
+    pub fn garbage_limit(&self) -> Option<GarbageLimit> {
+        self.garbage_limit
+    }
+
+    /// The bound on the destructor work of a hazard scan, if any.
+    pub fn reclaim_budget(&self) -> Option<ReclaimBudget> {
+        self.reclaim_budget
+    }
+
+    /// Whether a thread reclaims its own retired objects first, instead of flushing them to the
+    /// domain every `flush_interval` retirements.
+    ///
+    /// A thread then scans the hazards every `collect_interval` retirements, reclaims its own
+    /// unprotected objects, and pushes only the protected ones to the domain. The objects of the
+    /// domain are reclaimed along with them every few scans, or once the domain holds at least
+    /// `collect_interval` objects. This reduces the contention on the domain with many threads. It
+    /// has no effect while a background reclaimer is attached to the domain.
+    pub fn local_reclamation(&self) -> bool {
+        self.local_reclamation
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A builder of a [`Config`].
+#[derive(Debug, Clone)]
+pub struct ConfigBuilder {
+    config: Config,
+}
+
+impl ConfigBuilder {
+    /// Sets [`Config::flush_interval`].
+    pub fn flush_interval(mut self, interval: usize) -> Self {
+        self.config.flush_interval = interval;
+        self
+    }
+
+    /// Sets [`Config::collect_interval`].
+    pub fn collect_interval(mut self, interval: usize) -> Self {
+        self.config.collect_interval = interval;
+        self
+    }
+
+    /// Sets [`Config::garbage_limit`].
+    pub fn garbage_limit(mut self, limit: Option<GarbageLimit>) -> Self {
+        self.config.garbage_limit = limit;
+        self
+    }
+
+    /// Sets [`Config::reclaim_budget`].
+    pub fn reclaim_budget(mut self, budget: Option<ReclaimBudget>) -> Self {
+        self.config.reclaim_budget = budget;
+        self
+    }
+
+    /// Sets [`Config::local_reclamation`].
+    pub fn local_reclamation(mut self, local: bool) -> Self {
+        self.config.local_reclamation = local;
+        self
+    }
+
+    /// Validates the parameters and returns the configuration.
+    pub fn build(self) -> Result<Config, ConfigError> {
+        let config = self.config;
+        check_positive("flush_interval", config.flush_interval)?;
+        check_positive("collect_interval", config.collect_interval)?;
+        ReclaimBudget::check(config.reclaim_budget)?;
+        Ok(config)
+    }
+}
+
+/// A [`Config`] which can be read and replaced concurrently.
+///
+/// Each parameter is read independently, so a reader racing with `store` may observe a mix of the
+/// old and the new parameters. Any such mix is a valid configuration.
+#[derive(Debug)]
+pub(crate) struct AtomicConfig {
+    flush_interval: AtomicUsize,
+    collect_interval: AtomicUsize,
+    pub(crate) garbage_limit: AtomicGarbageLimit,
+    pub(crate) reclaim_budget: AtomicReclaimBudget,
+    local_reclamation: AtomicBool,
+}
+
+impl AtomicConfig {
+    pub(crate) const fn new(config: Config) -> Self {
+        Self {
+            flush_interval: AtomicUsize::new(config.flush_interval),
+            collect_interval: AtomicUsize::new(config.collect_interval),
+            garbage_limit: AtomicGarbageLimit::new(config.garbage_limit),
+            reclaim_budget: AtomicReclaimBudget::new(config.reclaim_budget),
+            local_reclamation: AtomicBool::new(config.local_reclamation),
+        }
+    }
+
+    pub(crate) fn load(&self) -> Config {
+        Config {
+            flush_interval: self.flush_interval(),
+            collect_interval: self.collect_interval(),
+            garbage_limit: self.garbage_limit.load(),
+            reclaim_budget: self.reclaim_budget.load(),
+            local_reclamation: self.local_reclamation(),
+        }
+    }
+
+    pub(crate) fn store(&self, config: Config) {
+        self.flush_interval
+            .store(config.flush_interval, Ordering::Relaxed);
+        self.collect_interval
+            .store(config.collect_interval, Ordering::Relaxed);
+        self.garbage_limit.store(config.garbage_limit);
+        self.reclaim_budget.store(config.reclaim_budget);
+        self.local_reclamation
+            .store(config.local_reclamation, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub(crate) fn flush_interval(&self) -> usize {
+        self.flush_interval.load(Ordering::Relaxed)
+    }
+
+    #[inline]
+    pub(crate) fn collect_interval(&self) -> usize {
+        self.collect_interval.load(Ordering::Relaxed)
+    }
+
+    #[inline]
+    pub(crate) fn local_reclamation(&self) -> bool {
+        self.local_reclamation.load(Ordering::Relaxed)
+    }
+}
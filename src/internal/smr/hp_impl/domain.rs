@@ -1,44 +1,223 @@
+use core::ptr;
 use core::sync::atomic::{AtomicUsize, Ordering};
+use core::time::Duration;
+use std::thread;
+use std::time::Instant;
 
 use crossbeam_utils::CachePadded;
 use rustc_hash::FxHashSet;
 
+use super::config::{AtomicConfig, Config};
 use super::hazard::ThreadRecords;
 use super::retire::RetiredList;
 use super::thread::Thread;
+use crate::internal::histogram::{AtomicHistogram, LatencyHistogram};
+use crate::internal::stats::{Counters, Stats};
 
 #[derive(Debug)]
 pub struct Domain {
     pub(crate) threads: CachePadded<ThreadRecords>,
     pub(crate) retireds: CachePadded<RetiredList>,
     pub(crate) num_garbages: CachePadded<AtomicUsize>,
+    pub(crate) config: AtomicConfig,
+    /// The statistics of the events without a thread.
+    pub(crate) stats: CachePadded<Counters>,
+    /// The latencies from retiring objects to reclaiming them.
+    pub(crate) latency: CachePadded<AtomicHistogram>,
+    /// The number of background reclaimers, which take over the reclamation from threads.
+    reclaimers: AtomicUsize,
+    /// The number of requests of barriers for threads to flush their retired objects.
+    pub(crate) flush_requests: AtomicUsize,
+    /// The numbers of reclamations started and finished, which tell a barrier whether a
+    /// reclamation holds retired objects popped from `retireds`.
+    pub(crate) reclaims_started: AtomicUsize,
+    pub(crate) reclaims_finished: AtomicUsize,
 }
 
 impl Domain {
     pub const fn new() -> Self {
+        Self::with_config(Config::new())
+    }
+
+    /// Creates a new domain with the given configuration.
+    pub const fn with_config(config: Config) -> Self {
         Self {
             threads: CachePadded::new(ThreadRecords::new()),
             retireds: CachePadded::new(RetiredList::new()),
             num_garbages: CachePadded::new(AtomicUsize::new(0)),
+            config: AtomicConfig::new(config),
+            stats: CachePadded::new(Counters::new()),
+            latency: CachePadded::new(AtomicHistogram::new()),
+            reclaimers: AtomicUsize::new(0),
+            flush_requests: AtomicUsize::new(0),
+            reclaims_started: AtomicUsize::new(0),
+            reclaims_finished: AtomicUsize::new(0),
         }
     }
 
+    /// Returns the current configuration.
+    pub fn config(&self) -> Config {
+        self.config.load()
+    }
+
+    /// Replaces the configuration. Threads observe the new configuration eventually.
+    pub fn set_config(&self, config: Config) {
+        self.config.store(config);
+    }
+
     pub fn collect_guarded_ptrs(&self, reclaimer: &Thread) -> FxHashSet<*mut u8> {
-        self.threads
+        let records = self.threads.pin();
+        records
             .iter()
             .flat_map(|thread| thread.iter(reclaimer))
             .collect()
     }
 
+    /// Blocks until every hazard pointer of the other threads, which protected an object at the
+    /// time of the call, has been reset or set to another object.
+    ///
+    /// A hazard pointer which is reset and then set to the same object again is waited for as
+    /// well.
+    pub fn synchronize(&self, reader: &Thread) {
+        let own = reader.hazards;
+        let records = self.threads.pin();
+        let mut pending: Vec<_> = records
+            .iter()
+            .filter(|record| !ptr::eq(*record, own))
+            .map(|record| (record, record.load_hazards(reader)))
+            .collect();
+        loop {
+            pending.retain_mut(|(record, hazards)| {
+                let current = record.load_hazards(reader);
+                for (hazard, current) in hazards.iter_mut().zip(current) {
+                    if *hazard != current {
+                        *hazard = ptr::null_mut();
+                    }
+                }
+                hazards.iter().any(|hazard| !hazard.is_null())
+            });
+            if pending.is_empty() {
+                return;
+            }
+            thread::yield_now();
+        }
+    }
+
+    /// Blocks until every object retired to this domain before the call has been reclaimed.
+    ///
+    /// The other threads flush their retired objects the next time they retire an object or
+    /// acquire a hazard pointer, so the call waits for each of them to do so, or to exit.
+    /// Meanwhile, `reclaimer` reclaims the objects as they become unprotected. Hence it blocks as
+    /// long as a registered thread stays idle, or a hazard pointer keeps protecting an object
+    /// retired before the call. Use [`try_barrier`] to bound the wait.
+    ///
+    /// [`try_barrier`]: Domain::try_barrier
+    pub fn barrier(&self, reclaimer: &Thread) {
+        self.barrier_until(reclaimer, None);
+    }
+
+    /// Like [`barrier`], but gives up once `timeout` has elapsed.
+    ///
+    /// Returns `true` if every object retired before the call has been reclaimed.
+    ///
+    /// [`barrier`]: Domain::barrier
+    pub fn try_barrier(&self, reclaimer: &Thread, timeout: Duration) -> bool {
+        self.barrier_until(reclaimer, Instant::now().checked_add(timeout))
+    }
+
+    fn barrier_until(&self, reclaimer: &Thread, deadline: Option<Instant>) -> bool {
+        let expired = || deadline.is_some_and(|deadline| Instant::now() >= deadline);
+        let request = self
+            .flush_requests
+            .fetch_add(1, Ordering::SeqCst)
+            .wrapping_add(1);
+        reclaimer.check_flush_request();
+        while !self.flushed_since(request) {
+            if expired() {
+                return false;
+            }
+            thread::yield_now();
+        }
+
+        // Every object retired before this point is in `retireds`, or held by a reclamation.
+        let start = Instant::now();
+        loop {
+            reclaimer.do_reclamation();
+
+            let started = self.reclaims_started.load(Ordering::SeqCst);
+            if started == self.reclaims_finished.load(Ordering::SeqCst) {
+                let retireds = self.retireds.pop_all();
+                let done = retireds.iter().all(|retired| retired.retired_at >= start);
+                if !retireds.is_empty() {
+                    self.retireds.push(retireds);
+                }
+                // No reclamation has popped objects before `pop_all` but pushed them back after.
+                if done && self.reclaims_started.load(Ordering::SeqCst) == started {
+                    return true;
+                }
+            }
+            if expired() {
+                return false;
+            }
+            thread::yield_now();
+        }
+    }
+
+    /// Returns `true` if every thread has flushed its retired objects since the `request`-th
+    /// flush request.
+    fn flushed_since(&self, request: usize) -> bool {
+        self.threads.pin().iter().all(|record| {
+            let ack = record.flush_ack.load(Ordering::Acquire);
+            (ack.wrapping_sub(request) as isize) >= 0
+        })
+    }
+
+    /// Returns the number of threads registered in the domain.
+    pub fn num_threads(&self) -> usize {
+        self.threads.pin().iter().count()
+    }
+
     pub fn num_garbages(&self) -> usize {
         self.num_garbages.load(Ordering::Acquire)
     }
+
+    pub(crate) fn attach_reclaimer(&self) {
+        self.reclaimers.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn detach_reclaimer(&self) {
+        self.reclaimers.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    /// Returns `true` if a background reclaimer reclaims the retired objects of this domain.
+    #[inline]
+    pub(crate) fn has_reclaimer(&self) -> bool {
+        self.reclaimers.load(Ordering::Relaxed) > 0
+    }
+
+    /// Returns the histogram of the latencies from retiring objects to reclaiming them.
+    pub fn latency_histogram(&self) -> LatencyHistogram {
+        self.latency.snapshot()
+    }
+
+    /// Returns the statistics of all threads, including the ones which have exited.
+    pub fn stats(&self) -> Stats {
+        let mut totals = self.stats.load();
+        totals += self.threads.stats();
+        totals.into()
+    }
+}
+
+impl Default for Domain {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Drop for Domain {
     fn drop(&mut self) {
-        for t in self.threads.iter() {
-            assert!(t.available.load(Ordering::Relaxed))
+        for t in self.threads.pin().iter() {
+            assert!(!t.is_in_use())
         }
         while !self.retireds.is_empty() {
             let mut retireds = self.retireds.pop_all();
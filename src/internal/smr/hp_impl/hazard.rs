@@ -1,19 +1,24 @@
 use core::marker::PhantomData;
-use core::sync::atomic::{AtomicBool, AtomicPtr, Ordering};
+use core::sync::atomic::{fence, AtomicPtr, AtomicU8, AtomicUsize, Ordering};
 use core::{mem, ptr};
+use std::sync::Mutex;
 
-use super::thread::Thread;
-use super::DEFAULT_THREAD;
+use super::thread::{Thread, ThreadPtr};
+use crate::internal::stats::{Counters, Totals};
 
 #[derive(Debug)]
 pub struct HazardPointer {
-    thread: *const Thread,
+    thread: ThreadPtr,
     idx: usize,
 }
 
 impl Default for HazardPointer {
     fn default() -> Self {
-        DEFAULT_THREAD.with(|t| HazardPointer::new(t))
+        // The thread-local thread may have been destroyed, e.g., if the hazard pointer is created
+        // in the destructor of another thread-local.
+        let thread = ThreadPtr::current();
+        let idx = unsafe { &*thread.as_ptr() }.acquire();
+        Self { thread, idx }
     }
 }
 
@@ -22,12 +27,17 @@ impl HazardPointer {
     #[inline(always)]
     pub fn new(thread: &Thread) -> Self {
         let idx = thread.acquire();
-        Self { thread, idx }
+        Self {
+            thread: ThreadPtr::borrowed(thread),
+            idx,
+        }
     }
 
     #[inline]
     unsafe fn hazard_array(&self) -> &HazardArray {
-        &*(*(*self.thread).hazards).hazptrs.load(Ordering::Acquire)
+        &*(*(*self.thread.as_ptr()).hazards)
+            .hazptrs
+            .load(Ordering::Acquire)
     }
 
     #[inline]
@@ -84,41 +94,91 @@ impl HazardPointer {
 
     #[inline]
     pub fn swap(x: &mut HazardPointer, y: &mut HazardPointer) {
-        mem::swap(&mut x.idx, &mut y.idx);
+        // The threads are swapped along with the slots, as either may be a temporary one.
+        mem::swap(x, y);
     }
 }
 
 impl Drop for HazardPointer {
     fn drop(&mut self) {
         self.reset_protection();
-        unsafe { (*(self.thread as *mut Thread)).release(self.idx) };
+        unsafe { (*(self.thread.as_ptr() as *mut Thread)).release(self.idx) };
+        // A temporary thread is dropped after releasing the slot, along with `self.thread`.
     }
 }
 
-/// Push-only list of recyclable thread records
+/// The initial number of hazard slots of a record.
+pub(crate) const HAZARD_ARRAY_INIT_SIZE: usize = 64;
+
+/// The number of consecutive compactions which find a record available before removing it.
+const RECORD_IDLE_COMPACTIONS: usize = 64;
+
+/// The states of a [`ThreadRecord`].
+const IN_USE: u8 = 0;
+const AVAILABLE: u8 = 1;
+const REMOVED: u8 = 2;
+
+/// List of recyclable thread records.
+///
+/// Records are pushed to the head, and the ones which stay available for long are removed by
+/// [`ThreadRecords::compact`], e.g., after a thread pool shrinks. A removed record is freed once
+/// no traversal, which is guarded by [`ThreadRecords::pin`], can reference it.
 #[derive(Debug)]
 pub(crate) struct ThreadRecords {
     head: AtomicPtr<ThreadRecord>,
+    /// The number of live [`RecordsGuard`]s.
+    readers: AtomicUsize,
+    /// The records which have been unlinked but may still be referenced by traversals. Locked
+    /// by a compaction, so that only one thread unlinks records at a time.
+    #[allow(clippy::vec_box)] // The records must not move, as traversals reference them.
+    removed: Mutex<Vec<Box<ThreadRecord>>>,
+    /// The statistics of the removed records.
+    stats: Counters,
 }
 
 /// Single-writer growable hazard pointer array.
-/// Does not shrink. (Use single-writer doubly linked list? see HP04)
+///
+/// The array is shrunk back to its initial size when the record is reused by another thread.
 #[derive(Debug)]
 pub struct ThreadRecord {
-    pub(crate) next: *mut ThreadRecord,
-    pub(crate) available: AtomicBool,
+    pub(crate) next: AtomicPtr<ThreadRecord>,
+    state: AtomicU8,
+    /// The number of consecutive compactions which have found this record available.
+    idle: AtomicUsize,
     pub(crate) hazptrs: AtomicPtr<HazardArray>,
+    /// The statistics of the threads which have owned this record.
+    pub(crate) stats: Counters,
+    /// The last flush request of a barrier for which the owner flushed its retired objects.
+    pub(crate) flush_ack: AtomicUsize,
 }
 
 pub(crate) type HazardArray = Vec<AtomicPtr<u8>>;
 
+/// Allocates a hazard array of `len` null slots.
+pub(crate) fn new_hazard_array(len: usize) -> *mut HazardArray {
+    let array = (0..len).map(|_| AtomicPtr::new(ptr::null_mut())).collect();
+    Box::into_raw(Box::new(array))
+}
+
 impl ThreadRecords {
     pub(crate) const fn new() -> Self {
         Self {
             head: AtomicPtr::new(ptr::null_mut()),
+            readers: AtomicUsize::new(0),
+            removed: Mutex::new(Vec::new()),
+            stats: Counters::new(),
         }
     }
 
+    /// Guards a traversal of the records, so that no removed record is freed meanwhile.
+    pub(crate) fn pin(&self) -> RecordsGuard<'_> {
+        self.readers.fetch_add(1, Ordering::Relaxed);
+        // Pairs with the fence in `compact`: either the compaction sees this guard, or the
+        // traversal does not see the records unlinked by the compaction.
+        fence(Ordering::SeqCst);
+        RecordsGuard { records: self }
+    }
+
     pub(crate) fn acquire(&self) -> (&ThreadRecord, Vec<usize>) {
         if let Some(avail) = self.try_acquire_available() {
             return avail;
@@ -127,34 +187,38 @@ impl ThreadRecords {
     }
 
     fn try_acquire_available(&self) -> Option<(&ThreadRecord, Vec<usize>)> {
+        let guard = self.pin();
         let mut cur = self.head.load(Ordering::Acquire);
         while let Some(cur_ref) = unsafe { cur.as_ref() } {
-            if cur_ref.available.load(Ordering::Relaxed)
+            if cur_ref.state.load(Ordering::Relaxed) == AVAILABLE
                 && cur_ref
-                    .available
-                    .compare_exchange(true, false, Ordering::Relaxed, Ordering::Relaxed)
+                    .state
+                    .compare_exchange(AVAILABLE, IN_USE, Ordering::Relaxed, Ordering::Relaxed)
                     .is_ok()
             {
                 let len = unsafe { &*cur_ref.hazptrs.load(Ordering::Relaxed) }.len();
+                // A record in use is not removed, so it outlives the guard.
                 return Some((cur_ref, (0..len).collect()));
             }
-            cur = cur_ref.next;
+            cur = cur_ref.next.load(Ordering::Acquire);
         }
+        drop(guard);
         None
     }
 
     fn acquire_new(&self) -> (&ThreadRecord, Vec<usize>) {
-        const HAZARD_ARRAY_INIT_SIZE: usize = 64;
-        let array = Vec::from(unsafe { mem::zeroed::<[AtomicPtr<u8>; HAZARD_ARRAY_INIT_SIZE]>() });
         let new = Box::leak(Box::new(ThreadRecord {
-            hazptrs: AtomicPtr::new(Box::into_raw(Box::new(array))),
-            next: ptr::null_mut(),
-            available: AtomicBool::new(false),
+            hazptrs: AtomicPtr::new(new_hazard_array(HAZARD_ARRAY_INIT_SIZE)),
+            next: AtomicPtr::new(ptr::null_mut()),
+            state: AtomicU8::new(IN_USE),
+            idle: AtomicUsize::new(0),
+            stats: Counters::new(),
+            flush_ack: AtomicUsize::new(0),
         }));
 
         let mut head = self.head.load(Ordering::Relaxed);
         loop {
-            new.next = head;
+            new.next.store(head, Ordering::Relaxed);
             match self
                 .head
                 .compare_exchange(head, new, Ordering::Release, Ordering::Relaxed)
@@ -166,30 +230,127 @@ impl ThreadRecords {
     }
 
     pub(crate) fn release(&self, rec: &ThreadRecord) {
-        rec.available.store(true, Ordering::Release);
+        rec.idle.store(0, Ordering::Relaxed);
+        rec.state.store(AVAILABLE, Ordering::Release);
+    }
+
+    /// Removes the records which have been available during the last
+    /// `RECORD_IDLE_COMPACTIONS` compactions, and frees the removed records if no traversal can
+    /// reference them. Does nothing if another thread is compacting the list.
+    pub(crate) fn compact(&self) {
+        let Ok(mut removed) = self.removed.try_lock() else {
+            return;
+        };
+
+        let mut prev: Option<&ThreadRecord> = None;
+        let mut cur = self.head.load(Ordering::Acquire);
+        while let Some(cur_ref) = unsafe { cur.as_ref() } {
+            let next = cur_ref.next.load(Ordering::Acquire);
+            if !cur_ref.try_remove() {
+                prev = Some(cur_ref);
+                cur = next;
+                continue;
+            }
+
+            // Only the compaction modifies `next` of a record, and pushes only modify `head`.
+            let unlinked = match prev {
+                Some(prev) => {
+                    prev.next.store(next, Ordering::Release);
+                    true
+                }
+                None => self
+                    .head
+                    .compare_exchange(cur, next, Ordering::Release, Ordering::Relaxed)
+                    .is_ok(),
+            };
+            if unlinked {
+                cur_ref.stats.drain_into(&self.stats);
+                removed.push(unsafe { Box::from_raw(cur) });
+            } else {
+                // A record has been pushed in front of it. Leave it to the next compaction.
+                cur_ref.state.store(AVAILABLE, Ordering::Release);
+                prev = Some(cur_ref);
+            }
+            cur = next;
+        }
+
+        fence(Ordering::SeqCst);
+        if self.readers.load(Ordering::Acquire) == 0 {
+            removed.clear();
+        }
     }
 
+    /// Sums up the statistics of all records, including the available and removed ones.
+    pub(crate) fn stats(&self) -> Totals {
+        let guard = self.pin();
+        let mut totals = self.stats.load();
+        let mut cur = self.head.load(Ordering::Acquire);
+        while let Some(cur_ref) = unsafe { cur.as_ref() } {
+            totals += cur_ref.stats.load();
+            cur = cur_ref.next.load(Ordering::Acquire);
+        }
+        drop(guard);
+        totals
+    }
+
+    /// Returns the number of records in the list, including the available ones.
+    #[cfg(test)]
+    pub(crate) fn num_records(&self) -> usize {
+        let _guard = self.pin();
+        let mut count = 0;
+        let mut cur = self.head.load(Ordering::Acquire);
+        while let Some(cur_ref) = unsafe { cur.as_ref() } {
+            count += 1;
+            cur = cur_ref.next.load(Ordering::Acquire);
+        }
+        count
+    }
+}
+
+impl Drop for ThreadRecords {
+    fn drop(&mut self) {
+        let mut cur = *self.head.get_mut();
+        while !cur.is_null() {
+            let mut record = unsafe { Box::from_raw(cur) };
+            cur = *record.next.get_mut();
+        }
+    }
+}
+
+/// A guard of a traversal of [`ThreadRecords`].
+pub(crate) struct RecordsGuard<'domain> {
+    records: &'domain ThreadRecords,
+}
+
+impl RecordsGuard<'_> {
+    /// Returns an iterator over the records in use.
     pub(crate) fn iter(&self) -> ThreadRecordsIter<'_> {
         ThreadRecordsIter {
-            cur: self.head.load(Ordering::Acquire).cast_const(),
+            cur: self.records.head.load(Ordering::Acquire).cast_const(),
             _marker: PhantomData,
         }
     }
 }
 
-pub(crate) struct ThreadRecordsIter<'domain> {
+impl Drop for RecordsGuard<'_> {
+    fn drop(&mut self) {
+        self.records.readers.fetch_sub(1, Ordering::Release);
+    }
+}
+
+pub(crate) struct ThreadRecordsIter<'guard> {
     cur: *const ThreadRecord,
-    _marker: PhantomData<&'domain ThreadRecord>,
+    _marker: PhantomData<&'guard ThreadRecord>,
 }
 
-impl<'domain> Iterator for ThreadRecordsIter<'domain> {
-    type Item = &'domain ThreadRecord;
+impl<'guard> Iterator for ThreadRecordsIter<'guard> {
+    type Item = &'guard ThreadRecord;
 
     fn next(&mut self) -> Option<Self::Item> {
         loop {
             let cur_ref = unsafe { self.cur.as_ref()? };
-            self.cur = cur_ref.next;
-            if !cur_ref.available.load(Ordering::Acquire) {
+            self.cur = cur_ref.next.load(Ordering::Acquire);
+            if cur_ref.is_in_use() {
                 return Some(cur_ref);
             }
         }
@@ -197,7 +358,34 @@ impl<'domain> Iterator for ThreadRecordsIter<'domain> {
 }
 
 impl ThreadRecord {
-    pub(crate) fn iter<'domain>(&self, reader: &Thread) -> ThreadHazardArrayIter<'domain> {
+    /// Returns `true` if a thread owns this record.
+    pub(crate) fn is_in_use(&self) -> bool {
+        self.state.load(Ordering::Acquire) == IN_USE
+    }
+
+    /// Claims this record for removal if it has been available long enough.
+    fn try_remove(&self) -> bool {
+        self.state.load(Ordering::Relaxed) == AVAILABLE
+            && self.idle.fetch_add(1, Ordering::Relaxed) + 1 >= RECORD_IDLE_COMPACTIONS
+            && self
+                .state
+                .compare_exchange(AVAILABLE, REMOVED, Ordering::Acquire, Ordering::Relaxed)
+                .is_ok()
+    }
+
+    /// Returns the current values of all hazard pointers of this record, including null ones.
+    pub(crate) fn load_hazards(&self, reader: &Thread) -> Vec<*mut u8> {
+        let mut hp = HazardPointer::new(reader);
+        let array = unsafe { &*hp.protect(&self.hazptrs) };
+        let hazards = array
+            .iter()
+            .map(|slot| slot.load(Ordering::Acquire))
+            .collect();
+        hp.reset_protection();
+        hazards
+    }
+
+    pub(crate) fn iter<'guard>(&self, reader: &Thread) -> ThreadHazardArrayIter<'guard> {
         let mut hp = HazardPointer::new(reader);
         let array = hp.protect(&self.hazptrs);
         ThreadHazardArrayIter {
@@ -209,14 +397,20 @@ impl ThreadRecord {
     }
 }
 
-pub(crate) struct ThreadHazardArrayIter<'domain> {
+impl Drop for ThreadRecord {
+    fn drop(&mut self) {
+        drop(unsafe { Box::from_raw(*self.hazptrs.get_mut()) });
+    }
+}
+
+pub(crate) struct ThreadHazardArrayIter<'guard> {
     array: *const [AtomicPtr<u8>],
     idx: usize,
     _hp: HazardPointer,
-    _marker: PhantomData<&'domain ()>,
+    _marker: PhantomData<&'guard ()>,
 }
 
-impl<'domain> Iterator for ThreadHazardArrayIter<'domain> {
+impl<'guard> Iterator for ThreadHazardArrayIter<'guard> {
     type Item = *mut u8;
 
     fn next(&mut self) -> Option<Self::Item> {
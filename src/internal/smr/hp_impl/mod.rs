@@ -1,18 +1,180 @@
+mod cohort;
+mod config;
 mod domain;
 mod hazard;
 mod retire;
+mod scan;
 mod thread;
 
+pub use cohort::Cohort;
+pub use config::{Config, ConfigBuilder};
 pub use hazard::HazardPointer;
-pub use thread::set_counts_between_flush;
+pub use scan::HazardSet;
 
+use std::cell::RefCell;
 use std::thread_local;
 
-use domain::Domain;
-pub use thread::Thread;
+use crate::internal::stats::{Event, Stats};
+
+pub use domain::Domain;
+pub use thread::{Participant, Thread, ThreadPtr};
 
 pub static DEFAULT_DOMAIN: Domain = Domain::new();
 
 thread_local! {
+    /// The current thread in the default domain, or `None` if the current thread is not
+    /// registered. See [`ThreadPtr::current`].
+    pub(crate) static CURRENT_THREAD: RefCell<Option<Box<Thread>>> = const { RefCell::new(None) };
+}
+
+thread_local! {
+    /// A separate [`Thread`] of the current thread in the default domain, registered on first use.
+    ///
+    /// This is not the thread used by `CsHP` and [`Participant`], which [`default_thread`] returns,
+    /// and is never unregistered before the thread exits.
+    #[deprecated(note = "Use `default_thread` instead")]
     pub static DEFAULT_THREAD: Box<Thread> = Box::new(Thread::new(&DEFAULT_DOMAIN));
 }
+
+/// Sets the flush interval of the default domain to `counts`, and the collection interval to
+/// twice of it.
+///
+/// # Panics
+///
+/// Panics if `counts` is zero.
+#[deprecated(note = "Use `Domain::set_config` with `ConfigBuilder::flush_interval` instead")]
+#[inline]
+pub fn set_counts_between_flush(counts: usize) {
+    #[allow(deprecated)]
+    crate::set_counts_between_flush_hp(counts);
+}
+
+/// Returns a pointer to the current thread in the default domain, registering the thread if it is
+/// not registered yet. See [`ThreadPtr`].
+#[inline]
+pub fn default_thread() -> ThreadPtr {
+    ThreadPtr::current()
+}
+
+/// Returns the statistics of the current thread in the default domain.
+pub fn thread_stats() -> Stats {
+    CURRENT_THREAD
+        .try_with(|slot| slot.borrow().as_ref().map(|thread| thread.stats()))
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+}
+
+/// Records an event of the current thread in the default domain.
+///
+/// If the current thread is not registered, e.g., it has only allocated objects, or it is already
+/// destroyed, the event is recorded in the domain itself. Recording never registers the thread.
+#[inline]
+pub(crate) fn record(event: Event, n: u64) {
+    let recorded = CURRENT_THREAD
+        .try_with(|slot| match slot.try_borrow().as_deref() {
+            Ok(Some(thread)) => {
+                thread.record(event, n);
+                true
+            }
+            _ => false,
+        })
+        .unwrap_or(false);
+    if !recorded {
+        DEFAULT_DOMAIN.stats.add_shared(event, n);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use atomic::Ordering;
+    use crossbeam_utils::thread;
+
+    use std::sync::Barrier;
+
+    use super::hazard::HAZARD_ARRAY_INIT_SIZE;
+    use super::{Domain, HazardPointer, Thread};
+    use crate::{AtomicRc, Cs, CsHP, Rc, Snapshot};
+
+    #[test]
+    fn retire_while_exiting() {
+        struct Foo(AtomicRc<usize, CsHP>);
+
+        impl Drop for Foo {
+            fn drop(&mut self) {
+                // Protect and retire after `CURRENT_THREAD` has been dropped. This must not panic.
+                let cs = CsHP::new();
+                let mut snapshot = Snapshot::new();
+                snapshot.load(&self.0, &cs);
+                self.0.store(Rc::new(1), Ordering::Relaxed, &cs);
+            }
+        }
+
+        thread_local! {
+            static FOO: Foo = Foo(AtomicRc::null());
+        }
+
+        thread::scope(|scope| {
+            scope.spawn(|_| {
+                // Initialize `FOO` and then `CURRENT_THREAD`.
+                FOO.with(|_| ());
+                FOO.with(|foo| foo.0.store(Rc::new(0), Ordering::Relaxed, &CsHP::new()));
+                // At thread exit, `CURRENT_THREAD` gets dropped first and `FOO` second.
+            });
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn compact_thread_records() {
+        const THREADS: usize = 32;
+        let domain = Domain::new();
+
+        // A thread pool grows, with a thread which grows its hazard array, and then shrinks.
+        let barrier = Barrier::new(THREADS);
+        thread::scope(|scope| {
+            for i in 0..THREADS {
+                let (domain, barrier) = (&domain, &barrier);
+                scope.spawn(move |_| {
+                    let thread = Thread::new(domain);
+                    let hazards = if i == 0 {
+                        4 * HAZARD_ARRAY_INIT_SIZE
+                    } else {
+                        1
+                    };
+                    let _hps: Vec<_> = (0..hazards).map(|_| HazardPointer::new(&thread)).collect();
+                    barrier.wait();
+                });
+            }
+        })
+        .unwrap();
+        assert_eq!(domain.threads.num_records(), THREADS);
+
+        // Every record is reused with a hazard array of the initial size.
+        let mut threads: Vec<_> = (0..THREADS).map(|_| Thread::new(&domain)).collect();
+        assert_eq!(domain.threads.num_records(), THREADS);
+        for record in domain.threads.pin().iter() {
+            let array = unsafe { &*record.hazptrs.load(Ordering::Acquire) };
+            assert_eq!(array.len(), HAZARD_ARRAY_INIT_SIZE);
+        }
+        let thread = threads.pop().unwrap();
+        drop(threads);
+
+        for _ in 0..100 {
+            unsafe { thread.retire(Box::into_raw(Box::new(0usize))) };
+            thread.eager_reclaim();
+        }
+        assert_eq!(domain.threads.num_records(), 1);
+        assert_eq!(domain.num_threads(), 1);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn deprecated_default_thread() {
+        use super::DEFAULT_THREAD;
+
+        let hp = DEFAULT_THREAD.with(|thread| HazardPointer::new(thread));
+        drop(hp);
+        assert!(DEFAULT_THREAD.with(|thread| thread.domain().num_threads()) >= 1);
+    }
+}
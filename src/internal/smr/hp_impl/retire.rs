@@ -1,6 +1,7 @@
 use core::ptr;
 use core::sync::atomic::{AtomicPtr, Ordering};
 use std::mem::{self, MaybeUninit};
+use std::time::Instant;
 
 const DATA_WORDS: usize = 3;
 type DeferredData = [usize; DATA_WORDS];
@@ -8,17 +9,19 @@ type DeferredData = [usize; DATA_WORDS];
 #[derive(Debug, Clone, Copy)]
 pub(crate) struct Retired {
     pub(crate) ptr: *mut u8,
+    pub(crate) retired_at: Instant,
     data: MaybeUninit<DeferredData>,
     call: unsafe fn(*mut u8),
 }
 
-// TODO: require <T: Send> in retire
+/// `Thread::defer` requires that it is safe for another thread to execute the deferred function.
 unsafe impl Send for Retired {}
 
 impl Retired {
     pub(crate) fn new<F: FnOnce()>(ptr: *mut u8, f: F) -> Self {
         let size = mem::size_of::<F>();
         let align = mem::align_of::<F>();
+        let retired_at = Instant::now();
 
         unsafe {
             if size <= mem::size_of::<DeferredData>() && align <= mem::align_of::<DeferredData>() {
@@ -32,6 +35,7 @@ impl Retired {
 
                 Self {
                     ptr,
+                    retired_at,
                     data,
                     call: call::<F>,
                 }
@@ -49,6 +53,7 @@ impl Retired {
 
                 Self {
                     ptr,
+                    retired_at,
                     data,
                     call: call::<F>,
                 }
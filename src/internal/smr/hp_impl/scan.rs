@@ -0,0 +1,132 @@
+use super::domain::Domain;
+use super::thread::Thread;
+
+/// The number of hazard pointers from which a Bloom filter is built.
+const BLOOM_MIN_HAZARDS: usize = 32;
+
+/// The number of bits of the Bloom filter per hazard pointer.
+const BLOOM_BITS_PER_HAZARD: usize = 16;
+
+/// The hazard pointers found by a scan of a domain, which a reclaimer probes for each retired
+/// object.
+///
+/// The pointers are kept sorted for binary search. With many of them, a Bloom filter rejects most
+/// of the unprotected objects before the search. The buffers are reused across scans, so a
+/// reclaimer scanning the same domain repeatedly does not allocate.
+#[derive(Debug, Default)]
+pub struct HazardSet {
+    hazards: Vec<*mut u8>,
+    /// The bits of the Bloom filter, or empty if the filter is not used.
+    filter: Vec<u64>,
+    /// The number of bits of a hash which index the filter.
+    filter_log: u32,
+}
+
+impl HazardSet {
+    pub const fn new() -> Self {
+        Self {
+            hazards: Vec::new(),
+            filter: Vec::new(),
+            filter_log: 0,
+        }
+    }
+
+    /// Replaces the contents with the hazard pointers of the threads in `domain`.
+    pub fn collect(&mut self, domain: &Domain, reader: &Thread) {
+        self.hazards.clear();
+        let records = domain.threads.pin();
+        for record in records.iter() {
+            self.hazards.extend(record.iter(reader));
+        }
+        drop(records);
+        self.hazards.sort_unstable();
+        self.hazards.dedup();
+        self.build_filter();
+    }
+
+    fn build_filter(&mut self) {
+        self.filter.clear();
+        if self.hazards.len() < BLOOM_MIN_HAZARDS {
+            return;
+        }
+
+        let bits = (self.hazards.len() * BLOOM_BITS_PER_HAZARD).next_power_of_two();
+        self.filter_log = bits.trailing_zeros();
+        self.filter.resize(bits / 64, 0);
+        for &hazard in &self.hazards {
+            let (first, second) = filter_bits(hazard, self.filter_log);
+            self.filter[first / 64] |= 1 << (first % 64);
+            self.filter[second / 64] |= 1 << (second % 64);
+        }
+    }
+
+    /// Returns `true` if `ptr` is protected by a hazard pointer.
+    #[inline]
+    pub fn contains(&self, ptr: *mut u8) -> bool {
+        if !self.filter.is_empty() {
+            let (first, second) = filter_bits(ptr, self.filter_log);
+            if self.filter[first / 64] & (1 << (first % 64)) == 0
+                || self.filter[second / 64] & (1 << (second % 64)) == 0
+            {
+                return false;
+            }
+        }
+        self.hazards.binary_search(&ptr).is_ok()
+    }
+
+    /// Returns the number of distinct hazard pointers.
+    pub fn len(&self) -> usize {
+        self.hazards.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hazards.is_empty()
+    }
+}
+
+/// Returns the two bits of a Bloom filter of `2^log` bits for `ptr`.
+#[inline]
+fn filter_bits(ptr: *mut u8, log: u32) -> (usize, usize) {
+    let hash = (ptr as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    let shift = 64 - log;
+    (
+        (hash >> shift) as usize,
+        (hash.rotate_left(32) >> shift) as usize,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HazardSet, BLOOM_MIN_HAZARDS};
+    use crate::hp_impl::{Domain, HazardPointer, Thread};
+
+    #[test]
+    fn contains() {
+        let domain = Domain::new();
+        let reader = Thread::new(&domain);
+        let mut set = HazardSet::new();
+
+        for hazards in [0, 3, BLOOM_MIN_HAZARDS, 4 * BLOOM_MIN_HAZARDS] {
+            let thread = Thread::new(&domain);
+            let protected = |i: usize| (i * 48 + 16) as *mut u8;
+            let hps: Vec<_> = (0..hazards)
+                .map(|i| {
+                    let mut hp = HazardPointer::new(&thread);
+                    hp.protect_raw(protected(i));
+                    hp
+                })
+                .collect();
+
+            set.collect(&domain, &reader);
+            // The reader protects its own hazard array while scanning it.
+            assert_eq!(set.len(), hazards + 1);
+            assert_eq!(set.filter.is_empty(), hazards + 1 < BLOOM_MIN_HAZARDS);
+            for i in 0..hazards {
+                assert!(set.contains(protected(i)));
+                assert!(!set.contains(protected(i).wrapping_add(8)));
+            }
+            assert!(!set.contains(protected(hazards)));
+            drop(hps);
+        }
+    }
+}
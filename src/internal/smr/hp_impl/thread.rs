@@ -1,27 +1,21 @@
+use core::ops::Deref;
 use core::ptr;
 use core::sync::atomic::{AtomicPtr, Ordering};
 use std::cell::{Cell, RefCell};
+use std::thread;
+use std::time::Instant;
 
+use super::cohort::Cohort;
 use super::domain::Domain;
-use super::hazard::ThreadRecord;
+use super::hazard::{new_hazard_array, ThreadRecord, HAZARD_ARRAY_INIT_SIZE};
 use super::retire::Retired;
-
-pub static mut COUNTS_BETWEEN_FLUSH: usize = 64;
-
-#[inline]
-pub fn set_counts_between_flush(counts: usize) {
-    unsafe { COUNTS_BETWEEN_FLUSH = counts };
-}
-
-#[inline]
-pub fn counts_between_flush() -> usize {
-    unsafe { COUNTS_BETWEEN_FLUSH }
-}
-
-#[inline]
-pub fn counts_between_collect() -> usize {
-    unsafe { COUNTS_BETWEEN_FLUSH * 2 }
-}
+use super::scan::HazardSet;
+use super::{CURRENT_THREAD, DEFAULT_DOMAIN};
+use crate::internal::config::Budget;
+use crate::internal::histogram::LatencyHistogram;
+use crate::internal::observer::notify;
+use crate::internal::stats::{Event, Stats, Totals};
+use crate::internal::unwind;
 
 pub struct Thread {
     pub(crate) domain: *const Domain,
@@ -32,12 +26,30 @@ pub struct Thread {
     pub(crate) count: Cell<usize>,
     pub(crate) in_recl: Cell<bool>,
     pub(crate) must_retry: Cell<bool>,
+    /// The hazard pointers found by the last scan, whose buffers are reused by the next one.
+    scanned: RefCell<HazardSet>,
+    /// The cohort which the thread has entered, or null.
+    pub(crate) cohort: Cell<*const Cohort>,
+    /// The objects retired to the entered cohort, which are pushed to it in a batch.
+    pub(crate) cohort_retired: RefCell<Vec<Retired>>,
+    /// The number of local reclamations, which drain the domain every `DOMAIN_DRAIN_INTERVAL`.
+    local_passes: Cell<usize>,
+    /// The number of hazard pointers and critical sections using this thread.
+    pub(crate) users: Cell<usize>,
+    /// The statistics of the record when this thread acquired it.
+    stats_base: Totals,
 }
 
 impl Thread {
     pub fn new(domain: &Domain) -> Self {
         let (thread, available_indices) = domain.threads.acquire();
-        Self {
+        let stats_base = thread.stats.load();
+        // The new thread has no retired objects to flush.
+        thread.flush_ack.store(
+            domain.flush_requests.load(Ordering::SeqCst),
+            Ordering::Release,
+        );
+        let thread = Self {
             domain,
             hazards: thread,
             available_indices: RefCell::new(available_indices),
@@ -45,13 +57,40 @@ impl Thread {
             count: Cell::new(0),
             in_recl: Cell::new(false),
             must_retry: Cell::new(false),
-        }
+            scanned: RefCell::new(HazardSet::new()),
+            cohort: Cell::new(ptr::null()),
+            cohort_retired: RefCell::new(Vec::new()),
+            local_passes: Cell::new(0),
+            users: Cell::new(0),
+            stats_base,
+        };
+        thread.shrink_array();
+        thread
+    }
+}
+
+/// The number of local reclamations per one which also reclaims the objects of the domain. See
+/// [`Config::local_reclamation`](super::Config::local_reclamation).
+const DOMAIN_DRAIN_INTERVAL: usize = 8;
+
+// stuff related to statistics
+impl Thread {
+    /// Returns the statistics of this thread.
+    pub fn stats(&self) -> Stats {
+        let mut totals = unsafe { &*self.hazards }.stats.load();
+        totals -= self.stats_base;
+        totals.into()
+    }
+
+    #[inline]
+    pub(crate) fn record(&self, event: Event, n: u64) {
+        unsafe { &*self.hazards }.stats.add(event, n);
     }
 }
 
 // stuff related to reclamation
 impl Thread {
-    fn domain(&self) -> &Domain {
+    pub(crate) fn domain(&self) -> &Domain {
         unsafe { &*self.domain }
     }
 
@@ -62,31 +101,79 @@ impl Thread {
         self.domain().retireds.push(self.retired.take())
     }
 
-    // NOTE: T: Send not required because we reclaim only locally.
+    // NOTE: Retired objects may be reclaimed by other threads, which pop them from the domain.
     #[inline]
-    pub unsafe fn retire<T>(&self, ptr: *mut T) {
+    pub unsafe fn retire<T: Send>(&self, ptr: *mut T) {
         self.defer(ptr as *mut _, move || unsafe { drop(Box::from_raw(ptr)) });
     }
 
+    /// Defers `f` until `ptr` is not protected by any hazard pointer.
+    ///
+    /// # Safety
+    ///
+    /// It should be safe for another thread to execute `f`.
     #[inline]
     pub unsafe fn defer<T, F>(&self, ptr: *mut T, f: F)
     where
         F: FnOnce(),
     {
+        self.check_flush_request();
+        if let Some(cohort) = self.cohort.get().as_ref() {
+            cohort.defer(self, ptr as *mut _, f);
+            return;
+        }
         self.retired
             .borrow_mut()
             .push(Retired::new(ptr as *mut _, f));
         let count = self.count.get().wrapping_add(1);
         self.count.set(count);
-        if count % counts_between_flush() == 0 {
+        let config = &self.domain().config;
+        if self.reclaims_locally() {
+            if count % config.collect_interval() == 0 {
+                self.do_reclamation();
+                if self.domain().num_garbages() > config.garbage_limit.high_water() {
+                    self.apply_backpressure();
+                }
+            }
+            return;
+        }
+        if count % config.flush_interval() == 0 {
             self.flush_retireds();
+            if self.domain().num_garbages() > config.garbage_limit.high_water() {
+                self.apply_backpressure();
+            }
         }
         // TODO: collecting right after pushing is kinda weird
-        if count % counts_between_collect() == 0 {
+        if count % config.collect_interval() == 0 && !self.domain().has_reclaimer() {
             self.do_reclamation();
         }
     }
 
+    /// Returns `true` if this thread reclaims its own retired objects first. See
+    /// [`Config::local_reclamation`](super::Config::local_reclamation).
+    #[inline]
+    fn reclaims_locally(&self) -> bool {
+        self.domain().config.local_reclamation() && !self.domain().has_reclaimer()
+    }
+
+    /// Flushes the retired objects if a barrier has requested it.
+    #[inline]
+    pub(crate) fn check_flush_request(&self) {
+        let request = self.domain().flush_requests.load(Ordering::Relaxed);
+        let record = unsafe { &*self.hazards };
+        if request != record.flush_ack.load(Ordering::Relaxed) {
+            self.ack_flush_request(request);
+        }
+    }
+
+    #[cold]
+    fn ack_flush_request(&self, request: usize) {
+        self.flush_retireds();
+        unsafe { &*self.hazards }
+            .flush_ack
+            .store(request, Ordering::Release);
+    }
+
     #[inline]
     pub fn eager_reclaim(&self) {
         self.count.set(0);
@@ -94,6 +181,32 @@ impl Thread {
         self.do_reclamation();
     }
 
+    /// Reclaims retired objects until the domain holds no more of them than the low-water mark
+    /// of the garbage limit, or the backpressure gives up.
+    #[cold]
+    fn apply_backpressure(&self) {
+        let limit = match self.domain().config.garbage_limit.load() {
+            Some(limit) => limit,
+            None => return,
+        };
+        if self.in_recl.get() {
+            // The backpressure can not make progress in the middle of a reclamation.
+            return;
+        }
+
+        let deadline = Instant::now().checked_add(limit.backpressure().max_wait());
+        loop {
+            self.do_reclamation();
+            if self.domain().num_garbages() <= limit.low_water() {
+                return;
+            }
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                return;
+            }
+            thread::yield_now();
+        }
+    }
+
     #[inline]
     pub(crate) fn do_reclamation(&self) {
         if self.in_recl.get() {
@@ -103,21 +216,50 @@ impl Thread {
         }
 
         self.in_recl.set(true);
+        // Deferred functions can not unwind out of `do_reclamation_inner`, but reset `in_recl`
+        // on any other panic as well, so that the thread can reclaim again.
+        let _reset = ResetOnDrop(&self.in_recl);
+        let mut budget = Budget::new(self.domain().config.reclaim_budget.load());
         loop {
-            self.do_reclamation_inner();
+            self.do_reclamation_inner(&mut budget);
 
-            if self.must_retry.get() {
-                self.must_retry.set(false);
-            } else {
+            // A retrial is dropped along with the rest of the work once the budget is exhausted.
+            if !self.must_retry.replace(false) || budget.is_exhausted() {
                 break;
             }
         }
-        self.in_recl.set(false);
     }
 
     #[inline]
-    pub(crate) fn do_reclamation_inner(&self) {
-        let retireds = self.domain().retireds.pop_all();
+    pub(crate) fn do_reclamation_inner(&self, budget: &mut Budget) {
+        let domain = self.domain();
+        domain.reclaims_started.fetch_add(1, Ordering::SeqCst);
+        self.reclaim_popped(budget);
+        domain.reclaims_finished.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn reclaim_popped(&self, budget: &mut Budget) {
+        let domain = self.domain();
+        let mut retireds = Vec::new();
+        let mut drain = true;
+        if self.reclaims_locally() {
+            // Counted as flushed, as the protected ones are pushed to the domain.
+            retireds = self.retired.take();
+            domain
+                .num_garbages
+                .fetch_add(retireds.len(), Ordering::AcqRel);
+            // The domain keeps the objects which were protected in the earlier reclamations, so
+            // it is drained only periodically, or once it holds as many as a local pass reclaims.
+            let passes = self.local_passes.get().wrapping_add(1);
+            self.local_passes.set(passes);
+            drain = retireds.is_empty()
+                || passes % DOMAIN_DRAIN_INTERVAL == 0
+                || domain.num_garbages().saturating_sub(retireds.len())
+                    >= domain.config.collect_interval();
+        }
+        if drain {
+            retireds.extend(domain.retireds.pop_all());
+        }
         let retireds_len = retireds.len();
         if retireds.is_empty() {
             return;
@@ -125,22 +267,47 @@ impl Thread {
 
         membarrier::heavy();
 
-        let guarded_ptrs = self.domain().collect_guarded_ptrs(self);
+        // Not borrowed by a nested reclamation, which `in_recl` prevents.
+        let mut guarded_ptrs = self.scanned.borrow_mut();
+        guarded_ptrs.collect(self.domain(), self);
+        self.domain().threads.compact();
+        self.record(Event::Scan, 1);
+        self.record(Event::HazardFound, guarded_ptrs.len() as u64);
+        // The objects retired by the deferred functions do not belong to the entered cohort.
+        let cohort = self.cohort.replace(ptr::null());
+        let now = Instant::now();
+        let mut latency = LatencyHistogram::default();
         let not_freed: Vec<Retired> = retireds
             .into_iter()
             .filter_map(|element| {
-                if guarded_ptrs.contains(&element.ptr) {
+                // The objects left over by an exhausted budget are kept for later reclamations.
+                if budget.is_exhausted() || guarded_ptrs.contains(element.ptr) {
                     Some(element)
                 } else {
-                    unsafe { element.call() };
+                    latency.record(now.saturating_duration_since(element.retired_at), 1);
+                    unwind::isolate(|| unsafe { element.call() });
+                    budget.spend();
                     None
                 }
             })
             .collect();
+        self.cohort.set(cohort);
+        self.domain().latency.merge(&latency);
+        let remaining = not_freed.len();
         self.domain()
             .num_garbages
-            .fetch_sub(retireds_len - not_freed.len(), Ordering::AcqRel);
+            .fetch_sub(retireds_len - remaining, Ordering::AcqRel);
         self.domain().retireds.push(not_freed);
+        notify!(on_reclamation(retireds_len - remaining, remaining));
+    }
+}
+
+/// Resets a flag when dropped.
+struct ResetOnDrop<'a>(&'a Cell<bool>);
+
+impl Drop for ResetOnDrop<'_> {
+    fn drop(&mut self) {
+        self.0.set(false);
     }
 }
 
@@ -149,6 +316,7 @@ impl Thread {
     /// acquire hazard slot
     #[inline(always)]
     pub(crate) fn acquire(&self) -> usize {
+        self.check_flush_request();
         let idx = self.available_indices.borrow_mut().pop();
         if let Some(idx) = idx {
             idx
@@ -177,6 +345,28 @@ impl Thread {
         self.available_indices.borrow_mut().extend(size..new_size)
     }
 
+    /// Replaces a hazard array grown by a previous owner of the record with one of the initial
+    /// size.
+    fn shrink_array(&self) {
+        let record = unsafe { &*self.hazards };
+        let array_ptr = record.hazptrs.load(Ordering::Relaxed);
+        if unsafe { &*array_ptr }.len() <= HAZARD_ARRAY_INIT_SIZE {
+            return;
+        }
+        // The previous owner has reset all of the slots.
+        record
+            .hazptrs
+            .store(new_hazard_array(HAZARD_ARRAY_INIT_SIZE), Ordering::Release);
+        *self.available_indices.borrow_mut() = (0..HAZARD_ARRAY_INIT_SIZE).collect();
+        // The old array is pushed to the domain rather than retired, so that building a thread
+        // never runs a reclamation, e.g., while the thread is being registered.
+        let retired = Retired::new(array_ptr as *mut u8, move || unsafe {
+            drop(Box::from_raw(array_ptr))
+        });
+        self.domain().num_garbages.fetch_add(1, Ordering::AcqRel);
+        self.domain().retireds.push(vec![retired]);
+    }
+
     /// release hazard slot
     pub(crate) fn release(&mut self, idx: usize) {
         self.available_indices.borrow_mut().push(idx);
@@ -194,3 +384,173 @@ impl Drop for Thread {
         self.domain().threads.release(unsafe { &*self.hazards });
     }
 }
+
+/// A pointer to a [`Thread`] in the default domain, which owns the thread if it is a temporary
+/// one.
+///
+/// The pointer usually borrows `CURRENT_THREAD`, and counts as a user of it. If it has already
+/// been destroyed, e.g., in the destructor of another thread-local, a temporary thread is
+/// registered instead, and is dropped along with the pointer, which flushes its retired objects
+/// to the domain. The lowest bit of the address marks the ownership.
+#[derive(Debug)]
+pub struct ThreadPtr(*const Thread);
+
+impl ThreadPtr {
+    /// Returns a pointer to no thread, which must not be dereferenced.
+    pub(crate) const fn null() -> Self {
+        Self(ptr::null())
+    }
+
+    pub(crate) fn borrowed(thread: &Thread) -> Self {
+        thread.users.set(thread.users.get() + 1);
+        Self(thread)
+    }
+
+    /// Returns a pointer to the current thread in the default domain, registering the thread if
+    /// it is not registered yet.
+    #[inline]
+    pub(crate) fn current() -> Self {
+        Self::try_current().unwrap_or_else(Self::temporary)
+    }
+
+    /// Returns a pointer to the current thread in the default domain as [`Self::current`], or
+    /// `None` if `CURRENT_THREAD` has already been destroyed.
+    #[inline]
+    pub(crate) fn try_current() -> Option<Self> {
+        CURRENT_THREAD
+            .try_with(|slot| {
+                let thread = slot.borrow().as_deref().map(Self::borrowed);
+                thread.unwrap_or_else(|| Self::register(slot))
+            })
+            .ok()
+    }
+
+    #[cold]
+    fn register(slot: &RefCell<Option<Box<Thread>>>) -> Self {
+        // The thread is built before borrowing the slot, which the code run meanwhile may read.
+        let thread = Box::new(Thread::new(&DEFAULT_DOMAIN));
+        Self::borrowed(slot.borrow_mut().insert(thread))
+    }
+
+    #[cold]
+    fn temporary() -> Self {
+        let thread = Box::into_raw(Box::new(Thread::new(&DEFAULT_DOMAIN)));
+        Self((thread as usize | 1) as *const Thread)
+    }
+
+    #[inline]
+    fn is_owned(&self) -> bool {
+        self.0 as usize & 1 != 0
+    }
+
+    #[inline]
+    pub(crate) fn as_ptr(&self) -> *const Thread {
+        (self.0 as usize & !1) as *const Thread
+    }
+}
+
+impl Deref for ThreadPtr {
+    type Target = Thread;
+
+    #[inline]
+    fn deref(&self) -> &Thread {
+        unsafe { &*self.as_ptr() }
+    }
+}
+
+impl Drop for ThreadPtr {
+    #[inline]
+    fn drop(&mut self) {
+        if self.is_owned() {
+            drop(unsafe { Box::from_raw(self.as_ptr() as *mut Thread) });
+        } else if let Some(thread) = unsafe { self.as_ptr().as_ref() } {
+            thread.users.set(thread.users.get() - 1);
+        }
+    }
+}
+
+/// A registration of the current thread in the default domain.
+///
+/// A thread is registered lazily on its first use of the domain, and unregistered when it exits.
+/// A participant registers the current thread up front instead, and unregisters it when dropped,
+/// e.g., when a worker of a thread pool parks, so that the thread no longer holds hazard slots
+/// which reclaiming threads scan. The thread is registered again on its next use.
+#[derive(Debug)]
+pub struct Participant {
+    thread: Option<ThreadPtr>,
+}
+
+impl Participant {
+    /// Registers the current thread in the default domain, unless it is already registered.
+    pub fn register() -> Self {
+        Self {
+            thread: Some(ThreadPtr::current()),
+        }
+    }
+
+    /// Returns `true` if the current thread is registered in the default domain.
+    pub fn is_registered() -> bool {
+        CURRENT_THREAD
+            .try_with(|slot| slot.borrow().is_some())
+            .unwrap_or(false)
+    }
+
+    /// Returns a pointer to the registered thread, which counts as a user of it.
+    pub(crate) fn thread(&self) -> ThreadPtr {
+        match &self.thread {
+            Some(thread) if !thread.is_owned() => ThreadPtr::borrowed(unsafe { &*thread.as_ptr() }),
+            // A temporary thread is not shared, as it is dropped along with the participant.
+            _ => ThreadPtr::current(),
+        }
+    }
+
+    /// Unregisters the current thread, as dropping the participant does, and flushes its retired
+    /// objects to the domain.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the thread is still used by a hazard pointer or a critical section, e.g., a live
+    /// `Snapshot` or `CsHP`. Dropping the participant leaves such a thread registered instead.
+    pub fn unregister(mut self) {
+        if let Err(users) = self.leave() {
+            panic!(
+                "`unregister` called while the thread has {} hazard pointers or critical sections",
+                users
+            );
+        }
+    }
+
+    /// Removes the current thread from `CURRENT_THREAD`, unless it is in use or has been
+    /// registered again meanwhile. Returns the number of users if the thread is in use.
+    fn leave(&mut self) -> Result<(), usize> {
+        let Some(thread) = self.thread.take() else {
+            return Ok(());
+        };
+        let address = thread.as_ptr();
+        drop(thread);
+        let left = CURRENT_THREAD.try_with(|slot| {
+            let Ok(mut slot) = slot.try_borrow_mut() else {
+                return Ok(None);
+            };
+            match slot.as_deref() {
+                Some(thread) if ptr::eq(thread, address) => match thread.users.get() {
+                    0 => Ok(slot.take()),
+                    users => Err(users),
+                },
+                _ => Ok(None),
+            }
+        });
+        match left {
+            // Dropped outside of the borrow, which flushes the retired objects.
+            Ok(thread) => thread.map(drop),
+            // The thread-local has been destroyed along with the thread.
+            Err(_) => Ok(()),
+        }
+    }
+}
+
+impl Drop for Participant {
+    fn drop(&mut self) {
+        let _ = self.leave();
+    }
+}
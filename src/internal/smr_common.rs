@@ -1,9 +1,14 @@
+use core::mem;
+use core::time::Duration;
+
 use atomic::Atomic;
 
-use crate::internal::utils::Counted;
+use crate::internal::observer::notify;
+use crate::internal::utils::CountedObject;
 use crate::internal::utils::EjectAction;
-use crate::internal::utils::TaggedCnt;
+use crate::internal::utils::Tagged;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RetireType {
     DecrementStrongCount,
     DecrementWeakCount,
@@ -13,14 +18,14 @@ pub enum RetireType {
 /// A SMR-specific acquired pointer trait.
 ///
 /// In most cases such as EBR, IBR and Hyaline, Acquired is equivalent to a simple tagged
-/// pointer pointing a Counted<T>.
+/// pointer pointing a counted object `T` (e.g., `Counted<U>`).
 ///
 /// However, for some pointer-based SMR, `Acquired` should contain other information like an
 /// index of a hazard slot. For this reason, a type for acquired pointer must be SMR-dependent,
 /// and every SMR must provide some reasonable interfaces to access and manage this pointer.
 pub trait Acquired<T> {
     fn clear(&mut self);
-    fn as_ptr(&self) -> TaggedCnt<T>;
+    fn as_ptr(&self) -> Tagged<T>;
     fn set_tag(&mut self, tag: usize);
     fn null() -> Self;
     fn is_null(&self) -> bool;
@@ -42,39 +47,78 @@ pub trait Cs {
     fn new() -> Self;
     unsafe fn without_epoch() -> Self;
     unsafe fn unprotected() -> Self;
-    fn create_object<T>(obj: T) -> *mut Counted<T>;
+    /// Allocates a new counted object.
+    ///
+    /// The object must be `Send`, because it is disposed and destroyed by whichever thread ejects
+    /// it, which is not necessarily the thread that retired it.
+    fn create_object<O: CountedObject + Send>(obj: O::Target) -> *mut O;
     /// Creates a shield for the given pointer, assuming that `ptr` is already protected by a
     /// reference count.
-    fn reserve<T>(&self, ptr: TaggedCnt<T>, shield: &mut Self::RawShield<T>);
-    fn protect_snapshot<T>(
+    fn reserve<O>(&self, ptr: Tagged<O>, shield: &mut Self::RawShield<O>);
+    fn protect_snapshot<O: CountedObject>(
         &self,
-        link: &Atomic<TaggedCnt<T>>,
-        shield: &mut Self::RawShield<T>,
+        link: &Atomic<Tagged<O>>,
+        shield: &mut Self::RawShield<O>,
     ) -> bool;
-    unsafe fn own_object<T>(ptr: *mut Counted<T>) -> Counted<T>;
-    unsafe fn retire<T>(&self, ptr: *mut Counted<T>, ret_type: RetireType);
+    unsafe fn own_object<O: CountedObject>(ptr: *mut O) -> O;
+    /// Retires an object, so that it is ejected with `ret_type` once no thread protects it.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must point to an object created by `create_object`. It may be ejected by another
+    /// thread, hence it must be `Send` as well.
+    unsafe fn retire<O: CountedObject + Send>(&self, ptr: *mut O, ret_type: RetireType);
     fn clear(&mut self);
     fn eager_reclaim(&mut self);
+    /// Blocks until every snapshot taken by another thread before the call has been released.
+    ///
+    /// # Panics
+    ///
+    /// For EBR, panics if the current thread is in a critical section.
+    fn synchronize();
+    /// Blocks until every object retired before the call has been ejected, along with the
+    /// objects retired by ejecting them.
+    ///
+    /// The other threads hand their retired objects over the next time they enter a critical
+    /// section or retire an object, so the call waits for each of them to do so, or to exit.
+    /// Hence it blocks as long as another registered thread stays idle. For HP, it also blocks as
+    /// long as a hazard pointer protects an object retired before the call.
+    ///
+    /// # Panics
+    ///
+    /// For EBR, panics if the current thread is in a critical section.
+    fn barrier();
+    /// Like [`Cs::barrier`], but gives up once `timeout` has elapsed.
+    ///
+    /// Returns `true` if every object retired before the call has been ejected.
+    ///
+    /// # Panics
+    ///
+    /// For EBR, panics if the current thread is in a critical section.
+    fn try_barrier(timeout: Duration) -> bool;
 
     #[inline]
-    unsafe fn dispose<T>(&self, cnt: &mut Counted<T>) {
+    unsafe fn dispose<O: CountedObject>(&self, cnt: &mut O) {
         debug_assert!(cnt.ref_count() == 0);
         cnt.dispose();
+        notify!(on_dispose(cnt as *mut O as *const u8));
         if cnt.release_weak() {
             self.destroy(cnt);
         }
     }
 
     #[inline]
-    unsafe fn destroy<T>(&self, cnt: &mut Counted<T>) {
+    unsafe fn destroy<O: CountedObject>(&self, cnt: &mut O) {
         debug_assert!(cnt.ref_count() == 0);
-        drop(Self::own_object(cnt));
+        notify!(on_destroy(cnt as *mut O as *const u8));
+        // The managed object is already disposed, so only its memory is released here.
+        mem::forget(Self::own_object(cnt));
     }
 
     /// Perform an eject action. This can correspond to any action that
     /// should be delayed until the ptr is no longer protected
     #[inline]
-    unsafe fn eject<T>(&self, cnt: &mut Counted<T>, ret_type: RetireType) {
+    unsafe fn eject<O: CountedObject + Send>(&self, cnt: &mut O, ret_type: RetireType) {
         match ret_type {
             RetireType::DecrementStrongCount => self.decrement_ref_cnt(cnt),
             RetireType::DecrementWeakCount => self.decrement_weak_cnt(cnt),
@@ -83,43 +127,81 @@ pub trait Cs {
     }
 
     #[inline]
-    unsafe fn increment_ref_cnt<T>(&self, cnt: &Counted<T>) -> bool {
+    unsafe fn increment_ref_cnt<O: CountedObject>(&self, cnt: &O) -> bool {
         cnt.add_ref()
     }
 
     #[inline]
-    unsafe fn increment_weak_cnt<T>(&self, cnt: &Counted<T>) -> bool {
+    unsafe fn increment_weak_cnt<O: CountedObject>(&self, cnt: &O) -> bool {
         cnt.add_weak()
     }
 
     #[inline]
-    unsafe fn decrement_ref_cnt<T>(&self, cnt: &mut Counted<T>) {
+    unsafe fn decrement_ref_cnt<O: CountedObject + Send>(&self, cnt: &mut O) {
         debug_assert!(cnt.ref_count() >= 1);
         let result = cnt.release_ref();
 
         match result {
             EjectAction::Nothing => {}
             EjectAction::Delay => self.retire(cnt, RetireType::Dispose),
-            EjectAction::Destroy => self.destroy(cnt),
+            EjectAction::Destroy => {
+                // `release_ref` has already disposed the object.
+                notify!(on_dispose(cnt as *mut O as *const u8));
+                self.destroy(cnt);
+            }
         }
     }
 
     #[inline]
-    unsafe fn decrement_weak_cnt<T>(&self, cnt: &mut Counted<T>) {
+    unsafe fn decrement_weak_cnt<O: CountedObject>(&self, cnt: &mut O) {
         debug_assert!(cnt.weak_count() >= 1);
         if cnt.release_weak() {
             self.destroy(cnt);
         }
     }
 
+    /// Releases the strong reference count held by an atomic link which is being dropped.
+    ///
+    /// If the link resides in an object which is being reclaimed, no thread can traverse it
+    /// anymore, and a backend may release the count without waiting for another grace period. By
+    /// default, the decrement is delayed.
+    ///
+    /// # Safety
+    ///
+    /// `cnt` must be the object pointed by the dropped link, which held a strong reference count.
+    #[inline]
+    unsafe fn release_link_ref_cnt<O: CountedObject + Send>(
+        &self,
+        _link: &Atomic<Tagged<O>>,
+        cnt: &mut O,
+    ) {
+        self.delayed_decrement_ref_cnt(cnt);
+    }
+
+    /// Releases the weak reference count held by an atomic link which is being dropped.
+    ///
+    /// See `release_link_ref_cnt` for details.
+    ///
+    /// # Safety
+    ///
+    /// `cnt` must be the object pointed by the dropped link, which held a weak reference count.
+    #[inline]
+    unsafe fn release_link_weak_cnt<O: CountedObject + Send>(
+        &self,
+        _link: &Atomic<Tagged<O>>,
+        cnt: &mut O,
+    ) {
+        self.delayed_decrement_weak_cnt(cnt);
+    }
+
     #[inline]
-    unsafe fn delayed_decrement_ref_cnt<T>(&self, cnt: &mut Counted<T>) {
+    unsafe fn delayed_decrement_ref_cnt<O: CountedObject + Send>(&self, cnt: &mut O) {
         debug_assert!(cnt.ref_count() >= 1);
         self.retire(cnt, RetireType::DecrementStrongCount);
     }
 
     #[inline]
-    unsafe fn delayed_decrement_weak_cnt<T>(&self, cnt: &mut Counted<T>) {
+    unsafe fn delayed_decrement_weak_cnt<O: CountedObject + Send>(&self, cnt: &mut O) {
         debug_assert!(cnt.weak_count() >= 1);
         self.retire(cnt, RetireType::DecrementWeakCount);
     }
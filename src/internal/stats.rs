@@ -0,0 +1,184 @@
+//! Reclamation statistics.
+//!
+//! Every participant of a backend (the epoch slot of an EBR participant, or a `ThreadRecord` of an
+//! HP domain) owns a set of `Counters`, which only its thread updates, without read-modify-write
+//! operations. A snapshot of a collector or a domain sums up the counters of all participants,
+//! along with the shared counters of the collector or the domain itself, which absorb the events
+//! of the participants that have left and the events of the threads which are not registered.
+
+use core::ops::{AddAssign, SubAssign};
+use core::sync::atomic::{AtomicU64, Ordering};
+
+/// A kind of event counted by `Counters`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Event {
+    Allocated,
+    Retired,
+    RetiredBytes,
+    Reclaimed,
+    ReclaimedBytes,
+    Disposed,
+    Destroyed,
+    EpochAdvance,
+    FailedAdvance,
+    Scan,
+    HazardFound,
+}
+
+const EVENTS: usize = Event::HazardFound as usize + 1;
+
+/// A snapshot of reclamation statistics.
+///
+/// The counters are updated with relaxed orderings and read one by one, so a snapshot taken while
+/// other threads are running is only approximately consistent.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    /// The number of objects allocated.
+    pub allocated: u64,
+    /// The number of objects retired, i.e., the number of ejections deferred until no thread
+    /// protects the object.
+    pub retired: u64,
+    /// The number of objects whose managed value was dropped.
+    pub disposed: u64,
+    /// The number of objects whose memory was released.
+    pub destroyed: u64,
+    /// The number of retired objects which are not ejected yet.
+    ///
+    /// For a single thread, this is the number of objects it retired minus the number of objects
+    /// it ejected, which may have been retired by other threads. Hence it is meaningful only for
+    /// a whole collector or domain.
+    pub unreclaimed: u64,
+    /// The size of the retired objects which are not ejected yet, in bytes. The same caveat as
+    /// `unreclaimed` applies.
+    pub unreclaimed_bytes: u64,
+    /// The number of successful attempts to advance the global epoch. Always zero for HP.
+    pub epoch_advances: u64,
+    /// The number of failed attempts to advance the global epoch, e.g., due to a participant
+    /// pinned in an older epoch. Always zero for HP.
+    pub failed_advances: u64,
+    /// The number of hazard scans performed. Always zero for EBR.
+    pub scans: u64,
+    /// The total number of hazard pointers found by the hazard scans. Always zero for EBR.
+    pub hazards_found: u64,
+}
+
+/// Raw values of `Counters`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Totals([u64; EVENTS]);
+
+impl Totals {
+    #[inline]
+    fn get(&self, event: Event) -> u64 {
+        self.0[event as usize]
+    }
+}
+
+impl AddAssign for Totals {
+    fn add_assign(&mut self, rhs: Self) {
+        for (lhs, rhs) in self.0.iter_mut().zip(rhs.0) {
+            *lhs = lhs.wrapping_add(rhs);
+        }
+    }
+}
+
+impl SubAssign for Totals {
+    fn sub_assign(&mut self, rhs: Self) {
+        for (lhs, rhs) in self.0.iter_mut().zip(rhs.0) {
+            *lhs = lhs.wrapping_sub(rhs);
+        }
+    }
+}
+
+impl From<Totals> for Stats {
+    fn from(totals: Totals) -> Self {
+        Self {
+            allocated: totals.get(Event::Allocated),
+            retired: totals.get(Event::Retired),
+            disposed: totals.get(Event::Disposed),
+            destroyed: totals.get(Event::Destroyed),
+            unreclaimed: totals
+                .get(Event::Retired)
+                .saturating_sub(totals.get(Event::Reclaimed)),
+            unreclaimed_bytes: totals
+                .get(Event::RetiredBytes)
+                .saturating_sub(totals.get(Event::ReclaimedBytes)),
+            epoch_advances: totals.get(Event::EpochAdvance),
+            failed_advances: totals.get(Event::FailedAdvance),
+            scans: totals.get(Event::Scan),
+            hazards_found: totals.get(Event::HazardFound),
+        }
+    }
+}
+
+/// Monotonic event counters.
+#[derive(Debug)]
+pub(crate) struct Counters([AtomicU64; EVENTS]);
+
+impl Counters {
+    pub(crate) const fn new() -> Self {
+        #[allow(clippy::declare_interior_mutable_const)]
+        const ZERO: AtomicU64 = AtomicU64::new(0);
+        Self([ZERO; EVENTS])
+    }
+
+    /// Adds `n` to the counter of `event`. Only the owner of the counters may call this.
+    #[inline]
+    pub(crate) fn add(&self, event: Event, n: u64) {
+        let counter = &self.0[event as usize];
+        counter.store(
+            counter.load(Ordering::Relaxed).wrapping_add(n),
+            Ordering::Relaxed,
+        );
+    }
+
+    /// Adds `n` to the counter of `event`, which may be updated by other threads concurrently.
+    #[inline]
+    pub(crate) fn add_shared(&self, event: Event, n: u64) {
+        self.0[event as usize].fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub(crate) fn load(&self) -> Totals {
+        let mut totals = Totals::default();
+        for (total, counter) in totals.0.iter_mut().zip(&self.0) {
+            *total = counter.load(Ordering::Relaxed);
+        }
+        totals
+    }
+
+    /// Moves the counts of `self` to `other`.
+    pub(crate) fn drain_into(&self, other: &Counters) {
+        for (counter, other) in self.0.iter().zip(&other.0) {
+            other.fetch_add(counter.swap(0, Ordering::Relaxed), Ordering::Relaxed);
+        }
+    }
+}
+
+impl Default for Counters {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot() {
+        let counters = Counters::new();
+        counters.add(Event::Retired, 3);
+        counters.add(Event::RetiredBytes, 48);
+        counters.add(Event::Reclaimed, 1);
+        counters.add(Event::ReclaimedBytes, 16);
+
+        let others = Counters::new();
+        others.add(Event::Reclaimed, 4);
+        counters.drain_into(&others);
+        assert_eq!(counters.load(), Totals::default());
+
+        let stats = Stats::from(others.load());
+        assert_eq!(stats.retired, 3);
+        assert_eq!(stats.unreclaimed, 0);
+        assert_eq!(stats.unreclaimed_bytes, 32);
+    }
+}
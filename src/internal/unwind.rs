@@ -0,0 +1,101 @@
+//! Isolation of panics raised by deferred functions.
+//!
+//! Deferred functions run destructors of user types in the middle of a reclamation batch. If one
+//! of them panics, the rest of the batch must still be processed, and the reclamation state of the
+//! thread must stay consistent. Hence every deferred function is run through `isolate`, which
+//! catches the panic and handles it according to the global `PanicPolicy`.
+
+use core::sync::atomic::{AtomicU8, Ordering};
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::process;
+use std::sync::Mutex;
+
+/// What to do when a deferred function, e.g., the destructor of a reclaimed object, panics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PanicPolicy {
+    /// Print the panic message and abort the process. This is the default.
+    Abort,
+    /// Print the panic message and continue with the rest of the reclamation.
+    LogAndContinue,
+    /// Store the panic payload and continue with the rest of the reclamation. Stored payloads
+    /// can be taken by [`take_reclamation_panics`] or rethrown by [`resume_reclamation_panic`].
+    ///
+    /// [`take_reclamation_panics`]: crate::take_reclamation_panics
+    /// [`resume_reclamation_panic`]: crate::resume_reclamation_panic
+    Collect,
+}
+
+static POLICY: AtomicU8 = AtomicU8::new(PanicPolicy::Abort as u8);
+
+/// The payloads of the panics caught under `PanicPolicy::Collect`.
+static PANICS: Mutex<Vec<Box<dyn Any + Send>>> = Mutex::new(Vec::new());
+
+#[inline]
+pub fn set_policy(policy: PanicPolicy) {
+    POLICY.store(policy as u8, Ordering::Relaxed);
+}
+
+#[inline]
+pub fn policy() -> PanicPolicy {
+    match POLICY.load(Ordering::Relaxed) {
+        0 => PanicPolicy::Abort,
+        1 => PanicPolicy::LogAndContinue,
+        _ => PanicPolicy::Collect,
+    }
+}
+
+/// Takes the payloads of the panics collected so far.
+pub fn take_panics() -> Vec<Box<dyn Any + Send>> {
+    // A panic can not occur while the lock is held, so the lock is never poisoned.
+    std::mem::take(&mut *PANICS.lock().unwrap())
+}
+
+/// Resumes unwinding with the earliest collected panic, if any. The other collected panics are
+/// discarded.
+pub fn resume_panic() {
+    if let Some(payload) = take_panics().into_iter().next() {
+        panic::resume_unwind(payload);
+    }
+}
+
+fn message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        msg
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg
+    } else {
+        "Box<dyn Any>"
+    }
+}
+
+/// Runs `f`, and handles a panic from it according to the current policy.
+///
+/// A panic unwinds out of the rest of `f`. In particular, if the destructor of an object panics
+/// while it is disposed, the object is never destroyed, and its block is leaked.
+#[inline]
+pub(crate) fn isolate<F: FnOnce()>(f: F) {
+    if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(f)) {
+        handle(payload);
+    }
+}
+
+#[cold]
+fn handle(payload: Box<dyn Any + Send>) {
+    match policy() {
+        PanicPolicy::Abort => {
+            eprintln!(
+                "cdrc-rs: a deferred function panicked: {}; aborting",
+                message(&*payload)
+            );
+            process::abort();
+        }
+        PanicPolicy::LogAndContinue => {
+            eprintln!(
+                "cdrc-rs: a deferred function panicked: {}; continuing",
+                message(&*payload)
+            );
+        }
+        PanicPolicy::Collect => PANICS.lock().unwrap().push(payload),
+    }
+}
@@ -1,10 +1,28 @@
 use core::mem;
+
+use atomic::Atomic;
 use std::{
     mem::ManuallyDrop,
     ptr,
-    sync::atomic::{fence, AtomicU32, Ordering},
+    sync::atomic::{fence, Ordering},
 };
 
+cfg_if::cfg_if! {
+    if #[cfg(feature = "u64-counts")] {
+        use std::sync::atomic::AtomicU64;
+
+        /// The integer type of strong and weak reference counts.
+        pub type CountValue = u64;
+        type AtomicCountValue = AtomicU64;
+    } else {
+        use std::sync::atomic::AtomicU32;
+
+        /// The integer type of strong and weak reference counts.
+        pub type CountValue = u32;
+        type AtomicCountValue = AtomicU32;
+    }
+}
+
 /// A wait-free atomic counter that supports increment and decrement, such that attempting to
 /// increment the counter from zero fails and does not perform the increment.
 ///
@@ -15,24 +33,49 @@ use std::{
 /// Assumption: The counter should never go negative. That is, the user should never decrement the
 /// counter by an amount greater than its current value
 ///
-/// Note: The counter steals the top two bits of the integer for book-keeping purposes. Hence the
-/// maximum representable value in the counter is 2^(8*32-2) - 1.
-pub(crate) struct Count {
-    x: AtomicU32,
+/// Note: The counter steals the top two bits of the integer for book-keeping purposes, and the
+/// third one marks the overflow range, which starts at `max_count()` = 2^(N-3), where N is the bit
+/// width of `CountValue`. Hence the maximum count which is not saturated is 2^(N-3) - 1.
+///
+/// Overflow: Once the counter reaches `max_count()`, it saturates. A saturated counter sticks
+/// around `saturated()` = 1.5 * 2^(N-3) and never drops to zero, so the managed object is leaked
+/// instead of being freed while it is still reachable. This is the same strategy as Linux's
+/// `refcount_t`. `saturated()` lies in the middle of the overflow range [2^(N-3), 2^(N-2)), so
+/// that racing increments and decrements can not move the counter out of it before it is reset
+/// to `saturated()`.
+pub struct Count {
+    x: AtomicCountValue,
+}
+
+impl Default for Count {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Count {
-    const fn zero_flag() -> u32 {
-        1 << (mem::size_of::<u32>() * 8 - 1)
+    const fn zero_flag() -> CountValue {
+        1 << (mem::size_of::<CountValue>() * 8 - 1)
+    }
+
+    const fn zero_pending_flag() -> CountValue {
+        1 << (mem::size_of::<CountValue>() * 8 - 2)
+    }
+
+    /// The smallest value which is regarded as an overflow.
+    pub(crate) const fn max_count() -> CountValue {
+        1 << (mem::size_of::<CountValue>() * 8 - 3)
     }
 
-    const fn zero_pending_flag() -> u32 {
-        1 << (mem::size_of::<u32>() * 8 - 2)
+    /// The value of a saturated counter.
+    pub(crate) const fn saturated() -> CountValue {
+        Self::max_count() + Self::max_count() / 2
     }
 
+    /// Creates a counter for a newly created object, which holds a single reference.
     pub fn new() -> Self {
         Self {
-            x: AtomicU32::new(1),
+            x: AtomicCountValue::new(1),
         }
     }
 
@@ -40,9 +83,15 @@ impl Count {
     ///
     /// Returns true if the increment was successful, i.e., the counter
     /// was not stuck at zero. Returns false if the counter was zero
-    pub fn increment(&self, add: u32, order: Ordering) -> bool {
+    pub(crate) fn increment(&self, add: CountValue, order: Ordering) -> bool {
         let val = self.x.fetch_add(add, order);
-        (val & Self::zero_flag()) == 0
+        if (val & Self::zero_flag()) != 0 {
+            return false;
+        }
+        if val + add >= Self::max_count() {
+            self.saturate();
+        }
+        true
     }
 
     /// Decrement the counter by the given amount. The counter must initially be
@@ -51,8 +100,13 @@ impl Count {
     ///
     /// Returns true if the counter was decremented to zero. Returns
     /// false if the counter was not decremented to zero
-    pub fn decrement(&self, sub: u32, order: Ordering) -> bool {
-        if self.x.fetch_sub(sub, order) == sub {
+    pub(crate) fn decrement(&self, sub: CountValue, order: Ordering) -> bool {
+        let val = self.x.fetch_sub(sub, order);
+        if val >= Self::max_count() && (val & Self::zero_flag()) == 0 {
+            self.saturate();
+            return false;
+        }
+        if val == sub {
             match self
                 .x
                 .compare_exchange(0, Self::zero_flag(), Ordering::SeqCst, Ordering::SeqCst)
@@ -71,7 +125,7 @@ impl Count {
 
     /// Loads the current value of the counter. If the current value is zero, it is guaranteed
     /// to remain zero until the counter is reset
-    pub fn load(&self, order: Ordering) -> u32 {
+    pub(crate) fn load(&self, order: Ordering) -> CountValue {
         let val = self.x.load(order);
         if val != 0 {
             return if (val & Self::zero_flag()) > 0 {
@@ -97,6 +151,12 @@ impl Count {
             }
         }
     }
+
+    /// Pins an overflowed counter to `saturated()`.
+    #[cold]
+    fn saturate(&self) {
+        self.x.store(Self::saturated(), Ordering::Relaxed);
+    }
 }
 
 pub enum EjectAction {
@@ -105,6 +165,47 @@ pub enum EjectAction {
     Destroy,
 }
 
+pub(crate) mod sealed {
+    use super::{CountValue, EjectAction};
+
+    /// Operations on a counted object and its reference counts.
+    ///
+    /// This trait is not exported, so that only this crate can manipulate reference counts.
+    pub trait CountedOps {
+        /// The type of the managed object.
+        type Target;
+
+        fn new(val: Self::Target) -> Self;
+        fn data(&self) -> &Self::Target;
+        fn data_mut(&mut self) -> &mut Self::Target;
+        /// Drops the managed object in place.
+        ///
+        /// # Safety
+        ///
+        /// The managed object must not be accessed after this call.
+        unsafe fn dispose(&mut self);
+        fn ref_count(&self) -> CountValue;
+        fn weak_count(&self) -> CountValue;
+        fn add_ref(&self) -> bool;
+        fn release_ref(&mut self) -> EjectAction;
+        fn add_weak(&self) -> bool;
+        fn release_weak(&self) -> bool;
+        fn into_inner(self) -> Self::Target;
+    }
+}
+
+use sealed::CountedOps;
+
+/// A heap object managed by reference-counted pointers, which consists of the managed object and
+/// its reference counts.
+///
+/// This trait is sealed. It is implemented by [`Counted`], [`CountedNoWeak`] and the types which
+/// implement [`IntrusiveCounted`].
+pub trait CountedObject: CountedOps {}
+
+/// A [`CountedObject`] with a weak reference count, which can be pointed by weak pointers.
+pub trait WeakCountedObject: CountedObject {}
+
 /// An instance of an object of type T with an atomic reference count.
 pub struct Counted<T> {
     storage: ManuallyDrop<T>,
@@ -112,8 +213,10 @@ pub struct Counted<T> {
     weak_cnt: Count,
 }
 
-impl<T> Counted<T> {
-    pub(crate) fn new(val: T) -> Self {
+impl<T> CountedOps for Counted<T> {
+    type Target = T;
+
+    fn new(val: T) -> Self {
         Self {
             storage: ManuallyDrop::new(val),
             ref_cnt: Count::new(),
@@ -121,27 +224,27 @@ impl<T> Counted<T> {
         }
     }
 
-    pub(crate) fn data(&self) -> &T {
+    fn data(&self) -> &T {
         &self.storage
     }
 
-    pub(crate) fn data_mut(&mut self) -> &mut T {
+    fn data_mut(&mut self) -> &mut T {
         &mut self.storage
     }
 
-    pub(crate) unsafe fn dispose(&mut self) {
+    unsafe fn dispose(&mut self) {
         ManuallyDrop::drop(&mut self.storage)
     }
 
-    pub(crate) fn ref_count(&self) -> u32 {
+    fn ref_count(&self) -> CountValue {
         self.ref_cnt.load(Ordering::SeqCst)
     }
 
-    pub(crate) fn weak_count(&self) -> u32 {
+    fn weak_count(&self) -> CountValue {
         self.weak_cnt.load(Ordering::SeqCst)
     }
 
-    pub(crate) fn add_ref(&self) -> bool {
+    fn add_ref(&self) -> bool {
         self.ref_cnt.increment(1, Ordering::SeqCst)
     }
 
@@ -149,7 +252,7 @@ impl<T> Counted<T> {
     /// the managed object will be destroyed, and the weak reference count will be decremented
     /// by one. If this causes the weak reference count to hit zero, returns true, indicating
     /// that the caller should delete this object.
-    pub(crate) fn release_ref(&mut self) -> EjectAction {
+    fn release_ref(&mut self) -> EjectAction {
         // A decrement-release + an acquire fence is recommended by Boost's documentation:
         // https://www.boost.org/doc/libs/1_57_0/doc/html/atomic/usage_examples.html
         // Alternatively, an acquire-release decrement would work, but might be less efficient
@@ -174,21 +277,280 @@ impl<T> Counted<T> {
         }
     }
 
-    pub(crate) fn add_weak(&self) -> bool {
+    fn add_weak(&self) -> bool {
         self.weak_cnt.increment(1, Ordering::Relaxed)
     }
 
     // Release weak references to the object. If this causes the weak reference count to hit zero,
     // returns true, indicating that the caller should delete this object.
-    pub(crate) fn release_weak(&self) -> bool {
+    fn release_weak(&self) -> bool {
         self.weak_cnt.decrement(1, Ordering::Release)
     }
 
-    pub(crate) fn into_inner(self) -> T {
+    fn into_inner(self) -> T {
+        ManuallyDrop::into_inner(self.storage)
+    }
+}
+
+impl<T> CountedObject for Counted<T> {}
+impl<T> WeakCountedObject for Counted<T> {}
+
+/// An instance of an object of type T with only a strong reference count.
+///
+/// Compared to [`Counted`], it saves the space of a weak count, and its last strong release
+/// destroys the object right away without inspecting weak references. In return, it can not be
+/// pointed by weak pointers.
+pub struct CountedNoWeak<T> {
+    storage: ManuallyDrop<T>,
+    ref_cnt: Count,
+}
+
+impl<T> CountedOps for CountedNoWeak<T> {
+    type Target = T;
+
+    fn new(val: T) -> Self {
+        Self {
+            storage: ManuallyDrop::new(val),
+            ref_cnt: Count::new(),
+        }
+    }
+
+    fn data(&self) -> &T {
+        &self.storage
+    }
+
+    fn data_mut(&mut self) -> &mut T {
+        &mut self.storage
+    }
+
+    unsafe fn dispose(&mut self) {
+        ManuallyDrop::drop(&mut self.storage)
+    }
+
+    fn ref_count(&self) -> CountValue {
+        self.ref_cnt.load(Ordering::SeqCst)
+    }
+
+    fn weak_count(&self) -> CountValue {
+        0
+    }
+
+    fn add_ref(&self) -> bool {
+        self.ref_cnt.increment(1, Ordering::SeqCst)
+    }
+
+    /// Release strong references to the object. As no weak references can exist, the object is
+    /// disposed as soon as the strong reference count reaches zero, and the caller should delete
+    /// it.
+    fn release_ref(&mut self) -> EjectAction {
+        if self.ref_cnt.decrement(1, Ordering::Release) {
+            fence(Ordering::Acquire);
+            unsafe { self.dispose() };
+            EjectAction::Destroy
+        } else {
+            EjectAction::Nothing
+        }
+    }
+
+    fn add_weak(&self) -> bool {
+        unreachable!("`CountedNoWeak` has no weak reference count")
+    }
+
+    fn release_weak(&self) -> bool {
+        unreachable!("`CountedNoWeak` has no weak reference count")
+    }
+
+    fn into_inner(self) -> T {
         ManuallyDrop::into_inner(self.storage)
     }
 }
 
+impl<T> CountedObject for CountedNoWeak<T> {}
+
+/// An object which embeds its own strong and weak reference counts.
+///
+/// Reference-counted pointers to an intrusively counted type `T` point to `T` itself, rather than
+/// to a [`Counted<T>`] wrapping it, so the type has full control over its layout. Use `T` as the
+/// counted object type of the pointers, e.g., `Rc<T, C, T>` and `AtomicWeak<T, C, T>`.
+///
+/// Both counts must be initialized by [`Count::new`] when the object is handed to `Rc::new`.
+///
+/// The object is never dropped by the pointers. When its strong count reaches zero, [`dispose`]
+/// is called to release the resources it owns, and when its weak count reaches zero as well, its
+/// memory is freed without running `Drop`. Hence fields which need to be dropped are typically
+/// wrapped in [`ManuallyDrop`] and dropped by [`dispose`].
+///
+/// # Safety
+///
+/// `ref_cnt` and `weak_cnt` must return two distinct counts embedded in `self`, which are not
+/// used for any other purpose.
+///
+/// [`dispose`]: IntrusiveCounted::dispose
+pub unsafe trait IntrusiveCounted {
+    fn ref_cnt(&self) -> &Count;
+    fn weak_cnt(&self) -> &Count;
+
+    /// Releases the resources owned by the object, except for its counts.
+    ///
+    /// # Safety
+    ///
+    /// This is called exactly once, when the strong count reaches zero. The counts must remain
+    /// accessible after this call.
+    unsafe fn dispose(&mut self);
+}
+
+impl<T: IntrusiveCounted> CountedOps for T {
+    type Target = T;
+
+    fn new(val: T) -> Self {
+        val
+    }
+
+    fn data(&self) -> &T {
+        self
+    }
+
+    fn data_mut(&mut self) -> &mut T {
+        self
+    }
+
+    unsafe fn dispose(&mut self) {
+        IntrusiveCounted::dispose(self)
+    }
+
+    fn ref_count(&self) -> CountValue {
+        self.ref_cnt().load(Ordering::SeqCst)
+    }
+
+    fn weak_count(&self) -> CountValue {
+        self.weak_cnt().load(Ordering::SeqCst)
+    }
+
+    fn add_ref(&self) -> bool {
+        self.ref_cnt().increment(1, Ordering::SeqCst)
+    }
+
+    /// Release strong references to the object, in the same way as `Counted` does.
+    fn release_ref(&mut self) -> EjectAction {
+        if self.ref_cnt().decrement(1, Ordering::Release) {
+            fence(Ordering::Acquire);
+            if self.weak_cnt().load(Ordering::Relaxed) == 1 {
+                unsafe { IntrusiveCounted::dispose(self) };
+                EjectAction::Destroy
+            } else {
+                EjectAction::Delay
+            }
+        } else {
+            EjectAction::Nothing
+        }
+    }
+
+    fn add_weak(&self) -> bool {
+        self.weak_cnt().increment(1, Ordering::Relaxed)
+    }
+
+    fn release_weak(&self) -> bool {
+        self.weak_cnt().decrement(1, Ordering::Release)
+    }
+
+    fn into_inner(self) -> T {
+        self
+    }
+}
+
+impl<T: IntrusiveCounted> CountedObject for T {}
+impl<T: IntrusiveCounted> WeakCountedObject for T {}
+
+/// A counted object which is asserted to be `Send`.
+///
+/// Every object pointed by a reference-counted pointer has been created by `Cs::create_object`,
+/// which requires `Send`. The pointer types do not repeat the bound, so that it does not spread
+/// to their users. Instead, they release their objects through this wrapper.
+#[repr(transparent)]
+pub(crate) struct AssertSend<O>(O);
+
+unsafe impl<O> Send for AssertSend<O> {}
+
+impl<O> AssertSend<O> {
+    /// # Safety
+    ///
+    /// `cnt` must be an object created by `Cs::create_object`.
+    #[inline(always)]
+    pub(crate) unsafe fn from_mut(cnt: &mut O) -> &mut Self {
+        &mut *(cnt as *mut O as *mut Self)
+    }
+
+    /// # Safety
+    ///
+    /// `link` must point to null or an object created by `Cs::create_object`.
+    #[inline(always)]
+    pub(crate) unsafe fn from_link(link: &Atomic<Tagged<O>>) -> &Atomic<Tagged<Self>> {
+        &*(link as *const Atomic<Tagged<O>> as *const Atomic<Tagged<Self>>)
+    }
+}
+
+impl<O: CountedOps> CountedOps for AssertSend<O> {
+    type Target = O::Target;
+
+    #[inline(always)]
+    fn new(val: Self::Target) -> Self {
+        Self(O::new(val))
+    }
+
+    #[inline(always)]
+    fn data(&self) -> &Self::Target {
+        self.0.data()
+    }
+
+    #[inline(always)]
+    fn data_mut(&mut self) -> &mut Self::Target {
+        self.0.data_mut()
+    }
+
+    #[inline(always)]
+    unsafe fn dispose(&mut self) {
+        self.0.dispose()
+    }
+
+    #[inline(always)]
+    fn ref_count(&self) -> CountValue {
+        self.0.ref_count()
+    }
+
+    #[inline(always)]
+    fn weak_count(&self) -> CountValue {
+        self.0.weak_count()
+    }
+
+    #[inline(always)]
+    fn add_ref(&self) -> bool {
+        self.0.add_ref()
+    }
+
+    #[inline(always)]
+    fn release_ref(&mut self) -> EjectAction {
+        self.0.release_ref()
+    }
+
+    #[inline(always)]
+    fn add_weak(&self) -> bool {
+        self.0.add_weak()
+    }
+
+    #[inline(always)]
+    fn release_weak(&self) -> bool {
+        self.0.release_weak()
+    }
+
+    #[inline(always)]
+    fn into_inner(self) -> Self::Target {
+        self.0.into_inner()
+    }
+}
+
+impl<O: CountedObject> CountedObject for AssertSend<O> {}
+impl<O: WeakCountedObject> WeakCountedObject for AssertSend<O> {}
+
 pub struct Tagged<T> {
     ptr: *mut T,
 }
@@ -245,10 +607,21 @@ impl<T> Tagged<T> {
         Self::new(with_tag(self.ptr, tag))
     }
 
+    /// Dereferences the pointer.
+    ///
+    /// # Safety
+    ///
+    /// The pointer must be non-null and point to a live object for the returned lifetime.
     pub unsafe fn deref<'g>(&self) -> &'g T {
         &*self.as_raw()
     }
 
+    /// Mutably dereferences the pointer.
+    ///
+    /// # Safety
+    ///
+    /// The pointer must be non-null and point to a live object for the returned lifetime, and no
+    /// other reference to the object may exist meanwhile.
     pub unsafe fn deref_mut<'g>(&mut self) -> &'g mut T {
         &mut *self.as_raw()
     }
@@ -266,9 +639,120 @@ fn with_tag<T>(ptr: *mut T, tag: usize) -> *mut T {
 
 pub type TaggedCnt<T> = Tagged<Counted<T>>;
 
-pub trait Pointer<T> {
-    fn as_ptr(&self) -> TaggedCnt<T>;
+pub trait Pointer<T, O = Counted<T>> {
+    fn as_ptr(&self) -> Tagged<O>;
     fn is_null(&self) -> bool {
         self.as_ptr().is_null()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+
+    use std::mem::ManuallyDrop;
+
+    use super::{
+        Count, CountValue, Counted, CountedNoWeak, CountedOps, EjectAction, IntrusiveCounted,
+    };
+
+    fn count_of(val: CountValue) -> Count {
+        let count = Count::new();
+        count.x.store(val, Ordering::Relaxed);
+        count
+    }
+
+    #[test]
+    fn near_overflow() {
+        let count = count_of(Count::max_count() - 2);
+        assert!(count.increment(1, Ordering::SeqCst));
+        assert_eq!(count.load(Ordering::SeqCst), Count::max_count() - 1);
+        assert!(!count.decrement(1, Ordering::SeqCst));
+        assert_eq!(count.load(Ordering::SeqCst), Count::max_count() - 2);
+    }
+
+    #[test]
+    fn saturate_on_increment() {
+        let count = count_of(Count::max_count() - 1);
+        assert!(count.increment(1, Ordering::SeqCst));
+        assert_eq!(count.load(Ordering::SeqCst), Count::saturated());
+
+        assert!(count.increment(1, Ordering::SeqCst));
+        assert_eq!(count.load(Ordering::SeqCst), Count::saturated());
+    }
+
+    #[test]
+    fn saturated_never_reaches_zero() {
+        let count = count_of(Count::saturated());
+        for _ in 0..1024 {
+            assert!(!count.decrement(1, Ordering::SeqCst));
+        }
+        assert_eq!(count.load(Ordering::SeqCst), Count::saturated());
+    }
+
+    #[test]
+    fn saturated_object_is_leaked() {
+        let mut cnt = Counted::new(Box::new(42));
+        cnt.ref_cnt = count_of(Count::max_count() - 1);
+        assert!(cnt.add_ref());
+        assert_eq!(cnt.ref_count(), Count::saturated());
+        assert!(matches!(cnt.release_ref(), EjectAction::Nothing));
+        assert_eq!(**cnt.data(), 42);
+        unsafe { cnt.dispose() };
+    }
+
+    #[test]
+    fn no_weak_release_destroys() {
+        assert!(std::mem::size_of::<CountedNoWeak<u32>>() < std::mem::size_of::<Counted<u32>>());
+
+        let mut cnt = CountedNoWeak::new(Box::new(42));
+        assert!(cnt.add_ref());
+        assert!(matches!(cnt.release_ref(), EjectAction::Nothing));
+        assert!(matches!(cnt.release_ref(), EjectAction::Destroy));
+        assert_eq!(cnt.ref_count(), 0);
+    }
+
+    struct Header {
+        ref_cnt: Count,
+        weak_cnt: Count,
+        value: ManuallyDrop<Box<usize>>,
+        disposed: usize,
+    }
+
+    unsafe impl IntrusiveCounted for Header {
+        fn ref_cnt(&self) -> &Count {
+            &self.ref_cnt
+        }
+
+        fn weak_cnt(&self) -> &Count {
+            &self.weak_cnt
+        }
+
+        unsafe fn dispose(&mut self) {
+            ManuallyDrop::drop(&mut self.value);
+            self.disposed += 1;
+        }
+    }
+
+    #[test]
+    fn intrusive_release() {
+        let mut obj = <Header as CountedOps>::new(Header {
+            ref_cnt: Count::new(),
+            weak_cnt: Count::new(),
+            value: ManuallyDrop::new(Box::new(42)),
+            disposed: 0,
+        });
+        assert!(obj.add_ref());
+        assert!(obj.add_weak());
+        assert_eq!(**obj.data().value, 42);
+
+        assert!(matches!(obj.release_ref(), EjectAction::Nothing));
+        assert!(matches!(obj.release_ref(), EjectAction::Delay));
+        assert_eq!(obj.disposed, 0);
+
+        unsafe { CountedOps::dispose(&mut obj) };
+        assert_eq!(obj.disposed, 1);
+        assert!(!obj.release_weak());
+        assert!(obj.release_weak());
+    }
+}
@@ -1,17 +1,198 @@
+use core::time::Duration;
+
+#[cfg(feature = "cycle-detector")]
+mod cycle;
 mod internal;
 mod strongs;
 mod weaks;
 
+#[cfg(feature = "cycle-detector")]
+pub use cycle::*;
 pub use internal::*;
 pub use strongs::*;
 pub use weaks::*;
 
+/// Sets the bag capacity and the collection interval of the default EBR collector to `counts`,
+/// and the epoch advancement interval to twice of it.
+///
+/// # Panics
+///
+/// Panics if `counts` is zero.
+#[deprecated(note = "Use `Collector::set_config` with `ConfigBuilder::bag_capacity` instead")]
 #[inline]
 pub fn set_counts_between_flush_ebr(counts: usize) {
-    internal::ebr_impl::set_bag_capacity(counts);
+    let collector = internal::ebr_impl::default_collector();
+    let config = collector
+        .config()
+        .into_builder()
+        .bag_capacity(counts)
+        .collect_interval(counts)
+        .advance_interval(counts.saturating_mul(2))
+        .build()
+        .expect("invalid EBR configuration");
+    collector.set_config(config);
 }
 
+/// Sets the flush interval of the default HP domain to `counts`, and the collection interval to
+/// twice of it.
+///
+/// # Panics
+///
+/// Panics if `counts` is zero.
+#[deprecated(note = "Use `Domain::set_config` with `ConfigBuilder::flush_interval` instead")]
 #[inline]
 pub fn set_counts_between_flush_hp(counts: usize) {
-    internal::hp_impl::set_counts_between_flush(counts);
+    let domain = &internal::hp_impl::DEFAULT_DOMAIN;
+    let config = domain
+        .config()
+        .into_builder()
+        .flush_interval(counts)
+        .collect_interval(counts.saturating_mul(2))
+        .build()
+        .expect("invalid HP configuration");
+    domain.set_config(config);
+}
+
+/// Sets the limit on the number of unreclaimed objects of the default EBR collector.
+#[inline]
+pub fn set_garbage_limit_ebr(limit: Option<GarbageLimit>) {
+    let collector = internal::ebr_impl::default_collector();
+    let config = collector
+        .config()
+        .into_builder()
+        .garbage_limit(limit)
+        .build()
+        .expect("invalid EBR configuration");
+    collector.set_config(config);
+}
+
+/// Sets the limit on the number of unreclaimed objects of the default HP domain.
+#[inline]
+pub fn set_garbage_limit_hp(limit: Option<GarbageLimit>) {
+    let domain = &internal::hp_impl::DEFAULT_DOMAIN;
+    let config = domain
+        .config()
+        .into_builder()
+        .garbage_limit(limit)
+        .build()
+        .expect("invalid HP configuration");
+    domain.set_config(config);
+}
+
+/// Blocks until every snapshot taken by another thread before the call has been released.
+///
+/// # Panics
+///
+/// For EBR, panics if the current thread is in a critical section.
+#[inline]
+pub fn synchronize<C: Cs>() {
+    C::synchronize();
+}
+
+/// Blocks until every object retired before the call has been ejected, so that the memory of the
+/// objects unreachable from any thread has been released. See [`Cs::barrier`].
+///
+/// # Panics
+///
+/// For EBR, panics if the current thread is in a critical section.
+#[inline]
+pub fn barrier<C: Cs>() {
+    C::barrier();
+}
+
+/// Like [`barrier`], but gives up once `timeout` has elapsed. Returns `true` if every object
+/// retired before the call has been ejected. See [`Cs::try_barrier`].
+///
+/// # Panics
+///
+/// For EBR, panics if the current thread is in a critical section.
+#[inline]
+pub fn try_barrier<C: Cs>(timeout: Duration) -> bool {
+    C::try_barrier(timeout)
+}
+
+/// Returns the participants of the default EBR collector which have kept the global epoch from
+/// advancing for longer than the stall threshold.
+#[inline]
+pub fn stalled_participants_ebr() -> Vec<internal::ebr_impl::StalledParticipant> {
+    internal::ebr_impl::default_collector().stalled_participants()
+}
+
+/// Returns the reclamation statistics of the default EBR collector.
+#[inline]
+pub fn stats_ebr() -> Stats {
+    internal::ebr_impl::default_collector().stats()
+}
+
+/// Returns the reclamation statistics of the default HP domain.
+#[inline]
+pub fn stats_hp() -> Stats {
+    internal::hp_impl::DEFAULT_DOMAIN.stats()
+}
+
+/// Returns the reclamation statistics of the current thread in the default EBR collector.
+#[inline]
+pub fn thread_stats_ebr() -> Stats {
+    internal::ebr_impl::thread_stats()
+}
+
+/// Returns the reclamation statistics of the current thread in the default HP domain.
+#[inline]
+pub fn thread_stats_hp() -> Stats {
+    internal::hp_impl::thread_stats()
+}
+
+/// Returns the histogram of the latencies from retiring objects to reclaiming them in the default
+/// EBR collector.
+///
+/// The objects retired into the same thread-local bag are accounted together with the age of the
+/// bag, which is an upper bound of their latencies.
+#[inline]
+pub fn latency_histogram_ebr() -> LatencyHistogram {
+    internal::ebr_impl::default_collector().latency_histogram()
+}
+
+/// Returns the histogram of the latencies from retiring objects to reclaiming them in the default
+/// HP domain.
+#[inline]
+pub fn latency_histogram_hp() -> LatencyHistogram {
+    internal::hp_impl::DEFAULT_DOMAIN.latency_histogram()
+}
+
+/// Enables or disables recycling of the memory blocks of reference-counted objects.
+///
+/// When enabled, blocks of destroyed objects are kept in per-thread free lists and reused by
+/// subsequent allocations instead of being returned to the global allocator.
+#[inline]
+pub fn set_object_pool_enabled(enabled: bool) {
+    internal::pool::set_enabled(enabled);
+}
+
+/// Returns the blocks kept in the global object pool and the current thread's pool to the global
+/// allocator.
+#[inline]
+pub fn purge_object_pool() {
+    internal::pool::purge();
+}
+
+/// Sets what to do when a deferred function, e.g., the destructor of a reclaimed object, panics.
+///
+/// A panicking deferred function never prevents the rest of a reclamation batch from being
+/// processed. See [`PanicPolicy`] for the available policies.
+#[inline]
+pub fn set_panic_policy(policy: PanicPolicy) {
+    internal::unwind::set_policy(policy);
+}
+
+/// Takes the payloads of the panics collected under [`PanicPolicy::Collect`].
+#[inline]
+pub fn take_reclamation_panics() -> Vec<Box<dyn std::any::Any + Send>> {
+    internal::unwind::take_panics()
+}
+
+/// Resumes unwinding with the earliest panic collected under [`PanicPolicy::Collect`], if any.
+/// The other collected panics are discarded.
+#[inline]
+pub fn resume_reclamation_panic() {
+    internal::unwind::resume_panic();
 }
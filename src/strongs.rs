@@ -7,25 +7,38 @@ use std::{
 use atomic::{Atomic, Ordering};
 use static_assertions::const_assert;
 
-use crate::{Acquired, AtomicWeak, Cs, Pointer, Tagged, TaggedCnt, Weak};
+use crate::internal::AssertSend;
+use crate::{
+    Acquired, AtomicWeak, CountValue, Counted, CountedNoWeak, CountedObject, Cs, Pointer, Tagged,
+    TaggedCnt, Weak, WeakCountedObject,
+};
 
 /// A result of unsuccessful `compare_exchange`.
 ///
 /// It returns the ownership of [`Rc`] pointer which was given as a parameter.
-pub struct CompareExchangeErrorRc<T, P> {
+pub struct CompareExchangeErrorRc<T, P, O: CountedObject<Target = T> = Counted<T>> {
     /// The `desired` which was given as a parameter of `compare_exchange`.
     pub desired: P,
     /// The current pointer value inside the atomic pointer.
-    pub current: TaggedCnt<T>,
+    pub current: Tagged<O>,
 }
 
-pub struct AtomicRc<T, C: Cs> {
-    link: Atomic<TaggedCnt<T>>,
+/// An [`AtomicRc`] whose objects have no weak reference count. See [`CountedNoWeak`].
+pub type AtomicRcNoWeak<T, C> = AtomicRc<T, C, CountedNoWeak<T>>;
+
+/// A [`Rc`] whose object has no weak reference count. See [`CountedNoWeak`].
+pub type RcNoWeak<T, C> = Rc<T, C, CountedNoWeak<T>>;
+
+/// A [`Snapshot`] of an object which has no weak reference count. See [`CountedNoWeak`].
+pub type SnapshotNoWeak<T, C> = Snapshot<T, C, CountedNoWeak<T>>;
+
+pub struct AtomicRc<T, C: Cs, O: CountedObject<Target = T> = Counted<T>> {
+    link: Atomic<Tagged<O>>,
     _marker: PhantomData<(T, *const C)>,
 }
 
-unsafe impl<T: Send + Sync, C: Cs> Send for AtomicRc<T, C> {}
-unsafe impl<T: Send + Sync, C: Cs> Sync for AtomicRc<T, C> {}
+unsafe impl<T: Send + Sync, C: Cs, O: CountedObject<Target = T>> Send for AtomicRc<T, C, O> {}
+unsafe impl<T: Send + Sync, C: Cs, O: CountedObject<Target = T>> Sync for AtomicRc<T, C, O> {}
 
 // Ensure that TaggedPtr<T> is 8-byte long,
 // so that lock-free atomic operations are possible.
@@ -33,11 +46,24 @@ const_assert!(Atomic::<TaggedCnt<u8>>::is_lock_free());
 const_assert!(mem::size_of::<TaggedCnt<u8>>() == mem::size_of::<usize>());
 const_assert!(mem::size_of::<Atomic<TaggedCnt<u8>>>() == mem::size_of::<AtomicUsize>());
 
-impl<T, C: Cs> AtomicRc<T, C> {
+impl<T, C: Cs, O: CountedObject<Target = T>> AtomicRc<T, C, O> {
+    /// Constructs a new `AtomicRc` pointing to a new object.
+    ///
+    /// The object must be `Send`, because it may be destroyed by another thread.
+    ///
+    /// ```compile_fail
+    /// use cdrc_rs::{AtomicRc, CsEBR};
+    ///
+    /// // `std::rc::Rc` is not `Send`.
+    /// let _ = AtomicRc::<_, CsEBR>::new(std::rc::Rc::new(0));
+    /// ```
     #[inline(always)]
-    pub fn new(obj: T) -> Self {
+    pub fn new(obj: T) -> Self
+    where
+        O: Send,
+    {
         Self {
-            link: Atomic::new(Rc::<T, C>::new(obj).into_raw()),
+            link: Atomic::new(Rc::<T, C, O>::new(obj).into_raw()),
             _marker: PhantomData,
         }
     }
@@ -56,18 +82,18 @@ impl<T, C: Cs> AtomicRc<T, C> {
     /// neither a SMR nor a reference count. To dereference, use `load` method of [`Snapshot`]
     /// instead.
     #[inline]
-    pub fn load(&self, order: Ordering) -> TaggedCnt<T> {
+    pub fn load(&self, order: Ordering) -> Tagged<O> {
         self.link.load(order)
     }
 
     #[inline]
-    pub fn store<P: StrongPtr<T, C>>(&self, ptr: P, order: Ordering, cs: &C) {
+    pub fn store<P: StrongPtr<T, C, O>>(&self, ptr: P, order: Ordering, cs: &C) {
         let new_ptr = ptr.as_ptr();
         ptr.into_ref_count();
         let old_ptr = self.link.swap(new_ptr, order);
         unsafe {
             if let Some(cnt) = old_ptr.as_raw().as_mut() {
-                cs.delayed_decrement_ref_cnt(cnt);
+                cs.delayed_decrement_ref_cnt(AssertSend::from_mut(cnt));
             }
         }
     }
@@ -76,7 +102,7 @@ impl<T, C: Cs> AtomicRc<T, C> {
     /// This operation is thread-safe.
     /// (It is equivalent to `exchange` from the original implementation.)
     #[inline(always)]
-    pub fn swap(&self, new: Rc<T, C>, order: Ordering, _: &C) -> Rc<T, C> {
+    pub fn swap(&self, new: Rc<T, C, O>, order: Ordering, _: &C) -> Rc<T, C, O> {
         let new_ptr = new.into_raw();
         Rc::from_raw(self.link.swap(new_ptr, order))
     }
@@ -87,14 +113,14 @@ impl<T, C: Cs> AtomicRc<T, C> {
     #[inline(always)]
     pub fn compare_exchange<'g, P>(
         &self,
-        expected: TaggedCnt<T>,
+        expected: Tagged<O>,
         desired: P,
         success: Ordering,
         failure: Ordering,
         _: &'g C,
-    ) -> Result<Rc<T, C>, CompareExchangeErrorRc<T, P>>
+    ) -> Result<Rc<T, C, O>, CompareExchangeErrorRc<T, P, O>>
     where
-        P: StrongPtr<T, C>,
+        P: StrongPtr<T, C, O>,
     {
         match self
             .link
@@ -123,9 +149,9 @@ impl<T, C: Cs> AtomicRc<T, C> {
         success: Ordering,
         failure: Ordering,
         _: &'g C,
-    ) -> Result<TaggedCnt<T>, CompareExchangeErrorRc<T, TaggedCnt<T>>>
+    ) -> Result<Tagged<O>, CompareExchangeErrorRc<T, Tagged<O>, O>>
     where
-        P: StrongPtr<T, C>,
+        P: StrongPtr<T, C, O>,
     {
         let desired = expected.as_ptr().with_tag(desired_tag);
         match self
@@ -146,15 +172,15 @@ impl<T, C: Cs> AtomicRc<T, C> {
     #[inline(always)]
     pub fn compare_exchange_protecting_current<'g, P>(
         &self,
-        expected: TaggedCnt<T>,
+        expected: Tagged<O>,
         mut desired: P,
-        current_snap: &mut Snapshot<T, C>,
+        current_snap: &mut Snapshot<T, C, O>,
         success: Ordering,
         failure: Ordering,
         cs: &'g C,
-    ) -> Result<Rc<T, C>, CompareExchangeErrorRc<T, P>>
+    ) -> Result<Rc<T, C, O>, CompareExchangeErrorRc<T, P, O>>
     where
-        P: StrongPtr<T, C>,
+        P: StrongPtr<T, C, O>,
     {
         loop {
             current_snap.load(self, cs);
@@ -178,12 +204,12 @@ impl<T, C: Cs> AtomicRc<T, C> {
     }
 
     #[inline(always)]
-    pub fn fetch_or<'g>(&self, tag: usize, order: Ordering, _: &'g C) -> TaggedCnt<T> {
-        // HACK: The size and alignment of `Atomic<TaggedCnt<T>>` will be same with `AtomicUsize`.
+    pub fn fetch_or<'g>(&self, tag: usize, order: Ordering, _: &'g C) -> Tagged<O> {
+        // HACK: The size and alignment of `Atomic<Tagged<O>>` will be same with `AtomicUsize`.
         // The equality of the sizes is checked by `const_assert!`.
         let link = unsafe { &*(&self.link as *const _ as *const AtomicUsize) };
         let prev = link.fetch_or(tag, order);
-        TaggedCnt::new(prev as *mut _)
+        Tagged::new(prev as *mut _)
     }
 
     #[inline]
@@ -196,29 +222,32 @@ impl<T, C: Cs> AtomicRc<T, C> {
     }
 }
 
-impl<T, C: Cs> Drop for AtomicRc<T, C> {
+impl<T, C: Cs, O: CountedObject<Target = T>> Drop for AtomicRc<T, C, O> {
     #[inline(always)]
     fn drop(&mut self) {
         let ptr = self.link.load(Ordering::Relaxed);
         unsafe {
             if let Some(cnt) = ptr.as_raw().as_mut() {
                 let cs = C::new();
-                cs.delayed_decrement_ref_cnt(cnt);
+                cs.release_link_ref_cnt(
+                    AssertSend::from_link(&self.link),
+                    AssertSend::from_mut(cnt),
+                );
             }
         }
     }
 }
 
-impl<T, C: Cs> Default for AtomicRc<T, C> {
+impl<T, C: Cs, O: CountedObject<Target = T>> Default for AtomicRc<T, C, O> {
     #[inline(always)]
     fn default() -> Self {
         Self::null()
     }
 }
 
-impl<T, C: Cs> From<Rc<T, C>> for AtomicRc<T, C> {
+impl<T, C: Cs, O: CountedObject<Target = T>> From<Rc<T, C, O>> for AtomicRc<T, C, O> {
     #[inline]
-    fn from(value: Rc<T, C>) -> Self {
+    fn from(value: Rc<T, C, O>) -> Self {
         let ptr = value.into_raw();
         Self {
             link: Atomic::new(ptr),
@@ -227,22 +256,22 @@ impl<T, C: Cs> From<Rc<T, C>> for AtomicRc<T, C> {
     }
 }
 
-pub struct Rc<T, C: Cs> {
-    ptr: TaggedCnt<T>,
+pub struct Rc<T, C: Cs, O: CountedObject<Target = T> = Counted<T>> {
+    ptr: Tagged<O>,
     _marker: PhantomData<(T, *const C)>,
 }
 
-unsafe impl<T: Send + Sync, C: Cs> Send for Rc<T, C> {}
-unsafe impl<T: Send + Sync, C: Cs> Sync for Rc<T, C> {}
+unsafe impl<T: Send + Sync, C: Cs, O: CountedObject<Target = T>> Send for Rc<T, C, O> {}
+unsafe impl<T: Send + Sync, C: Cs, O: CountedObject<Target = T>> Sync for Rc<T, C, O> {}
 
-impl<T, C: Cs> Rc<T, C> {
+impl<T, C: Cs, O: CountedObject<Target = T>> Rc<T, C, O> {
     #[inline(always)]
     pub fn null() -> Self {
-        Self::from_raw(TaggedCnt::null())
+        Self::from_raw(Tagged::null())
     }
 
     #[inline(always)]
-    pub(crate) fn from_raw(ptr: TaggedCnt<T>) -> Self {
+    pub(crate) fn from_raw(ptr: Tagged<O>) -> Self {
         Self {
             ptr,
             _marker: PhantomData,
@@ -250,7 +279,7 @@ impl<T, C: Cs> Rc<T, C> {
     }
 
     #[inline(always)]
-    pub fn from_snapshot<'g>(ptr: &Snapshot<T, C>, cs: &'g C) -> Self {
+    pub fn from_snapshot<'g>(ptr: &Snapshot<T, C, O>, cs: &'g C) -> Self {
         let rc = Self {
             ptr: ptr.as_ptr(),
             _marker: PhantomData,
@@ -263,11 +292,24 @@ impl<T, C: Cs> Rc<T, C> {
         rc
     }
 
+    /// Constructs a new `Rc` pointing to a new object.
+    ///
+    /// The object must be `Send`, because it may be destroyed by another thread.
+    ///
+    /// ```compile_fail
+    /// use cdrc_rs::{CsHP, Rc};
+    ///
+    /// // `std::rc::Rc` is not `Send`.
+    /// let _ = Rc::<_, CsHP>::new(std::rc::Rc::new(0));
+    /// ```
     #[inline(always)]
-    pub fn new(obj: T) -> Self {
+    pub fn new(obj: T) -> Self
+    where
+        O: Send,
+    {
         let ptr = C::create_object(obj);
         Self {
-            ptr: TaggedCnt::new(ptr),
+            ptr: Tagged::new(ptr),
             _marker: PhantomData,
         }
     }
@@ -287,12 +329,12 @@ impl<T, C: Cs> Rc<T, C> {
     }
 
     #[inline(always)]
-    pub fn ref_count(&self) -> u32 {
+    pub fn ref_count(&self) -> CountValue {
         unsafe { self.ptr.deref().ref_count() }
     }
 
     #[inline(always)]
-    pub fn weak_count(&self) -> u32 {
+    pub fn weak_count(&self) -> CountValue {
         unsafe { self.ptr.deref().weak_count() }
     }
 
@@ -308,7 +350,7 @@ impl<T, C: Cs> Rc<T, C> {
     }
 
     #[inline]
-    pub(crate) fn into_raw(self) -> TaggedCnt<T> {
+    pub(crate) fn into_raw(self) -> Tagged<O> {
         let new_ptr = self.as_ptr();
         // Skip decrementing the ref count.
         forget(self);
@@ -319,7 +361,7 @@ impl<T, C: Cs> Rc<T, C> {
     pub fn finalize(self, cs: &C) {
         unsafe {
             if let Some(cnt) = self.ptr.as_raw().as_mut() {
-                cs.delayed_decrement_ref_cnt(cnt);
+                cs.delayed_decrement_ref_cnt(AssertSend::from_mut(cnt));
             }
         }
         forget(self);
@@ -335,47 +377,49 @@ impl<T, C: Cs> Rc<T, C> {
     }
 }
 
-impl<T, C: Cs> Default for Rc<T, C> {
+impl<T, C: Cs, O: CountedObject<Target = T>> Default for Rc<T, C, O> {
     #[inline]
     fn default() -> Self {
         Self::null()
     }
 }
 
-impl<T, C: Cs> Drop for Rc<T, C> {
+impl<T, C: Cs, O: CountedObject<Target = T>> Drop for Rc<T, C, O> {
     #[inline(always)]
     fn drop(&mut self) {
         unsafe {
             if let Some(cnt) = self.ptr.as_raw().as_mut() {
                 let cs = C::new();
-                cs.delayed_decrement_ref_cnt(cnt);
+                cs.delayed_decrement_ref_cnt(AssertSend::from_mut(cnt));
             }
         }
     }
 }
 
-impl<T, C: Cs> PartialEq for Rc<T, C> {
+impl<T, C: Cs, O: CountedObject<Target = T>> PartialEq for Rc<T, C, O> {
     #[inline(always)]
     fn eq(&self, other: &Self) -> bool {
         self.ptr == other.ptr
     }
 }
 
-pub struct Snapshot<T, C: Cs> {
-    // Hint: `C::Acquired` is usually a wrapper struct containing `TaggedCnt`.
-    acquired: C::RawShield<T>,
+pub struct Snapshot<T, C: Cs, O: CountedObject<Target = T> = Counted<T>> {
+    // Hint: `C::Acquired` is usually a wrapper struct containing `Tagged`.
+    acquired: C::RawShield<O>,
+    _marker: PhantomData<T>,
 }
 
-impl<T, C: Cs> Snapshot<T, C> {
+impl<T, C: Cs, O: CountedObject<Target = T>> Snapshot<T, C, O> {
     #[inline(always)]
     pub fn new() -> Self {
         Self {
             acquired: <C as Cs>::RawShield::null(),
+            _marker: PhantomData,
         }
     }
 
     #[inline]
-    pub fn load(&mut self, from: &AtomicRc<T, C>, cs: &C) {
+    pub fn load(&mut self, from: &AtomicRc<T, C, O>, cs: &C) {
         let ok = cs.protect_snapshot(&from.link, &mut self.acquired);
         debug_assert!(
             ok,
@@ -384,17 +428,23 @@ impl<T, C: Cs> Snapshot<T, C> {
     }
 
     #[inline]
-    pub fn load_from_weak(&mut self, from: &AtomicWeak<T, C>, cs: &C) -> bool {
+    pub fn load_from_weak(&mut self, from: &AtomicWeak<T, C, O>, cs: &C) -> bool
+    where
+        O: WeakCountedObject,
+    {
         cs.protect_snapshot(&from.link, &mut self.acquired)
     }
 
     #[inline]
-    pub fn protect(&mut self, ptr: &Rc<T, C>, cs: &C) {
+    pub fn protect(&mut self, ptr: &Rc<T, C, O>, cs: &C) {
         cs.reserve(ptr.as_ptr(), &mut self.acquired);
     }
 
     #[inline]
-    pub fn protect_weak(&mut self, ptr: &Weak<T, C>, cs: &C) -> bool {
+    pub fn protect_weak(&mut self, ptr: &Weak<T, C, O>, cs: &C) -> bool
+    where
+        O: WeakCountedObject,
+    {
         cs.reserve(ptr.as_ptr(), &mut self.acquired);
         if !self.acquired.is_null() {
             if unsafe { self.acquired.as_ptr().deref() }.ref_count() == 0 {
@@ -416,7 +466,7 @@ impl<T, C: Cs> Snapshot<T, C> {
     }
 
     #[inline]
-    pub fn with_tag<'s>(&'s self, tag: usize) -> TaggedSnapshot<'s, T, C> {
+    pub fn with_tag<'s>(&'s self, tag: usize) -> TaggedSnapshot<'s, T, C, O> {
         TaggedSnapshot { inner: self, tag }
     }
 
@@ -427,7 +477,7 @@ impl<T, C: Cs> Snapshot<T, C> {
 
     #[inline]
     pub fn swap(p1: &mut Self, p2: &mut Self) {
-        <C::RawShield<T> as Acquired<T>>::swap(&mut p1.acquired, &mut p2.acquired)
+        <C::RawShield<O> as Acquired<O>>::swap(&mut p1.acquired, &mut p2.acquired)
     }
 
     #[inline]
@@ -436,21 +486,21 @@ impl<T, C: Cs> Snapshot<T, C> {
     }
 }
 
-impl<T, C: Cs> Default for Snapshot<T, C> {
+impl<T, C: Cs, O: CountedObject<Target = T>> Default for Snapshot<T, C, O> {
     #[inline]
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl<T, C: Cs> Drop for Snapshot<T, C> {
+impl<T, C: Cs, O: CountedObject<Target = T>> Drop for Snapshot<T, C, O> {
     #[inline(always)]
     fn drop(&mut self) {
         self.acquired.clear();
     }
 }
 
-impl<T, C: Cs> PartialEq for Snapshot<T, C> {
+impl<T, C: Cs, O: CountedObject<Target = T>> PartialEq for Snapshot<T, C, O> {
     #[inline(always)]
     fn eq(&self, other: &Self) -> bool {
         self.acquired.eq(&other.acquired)
@@ -458,40 +508,40 @@ impl<T, C: Cs> PartialEq for Snapshot<T, C> {
 }
 
 /// A reference of a [`Snapshot`] with a overwriting tag value.
-pub struct TaggedSnapshot<'s, T, C: Cs> {
-    pub(crate) inner: &'s Snapshot<T, C>,
+pub struct TaggedSnapshot<'s, T, C: Cs, O: CountedObject<Target = T> = Counted<T>> {
+    pub(crate) inner: &'s Snapshot<T, C, O>,
     pub(crate) tag: usize,
 }
 
-impl<T, C: Cs> Pointer<T> for Rc<T, C> {
+impl<T, C: Cs, O: CountedObject<Target = T>> Pointer<T, O> for Rc<T, C, O> {
     #[inline]
-    fn as_ptr(&self) -> TaggedCnt<T> {
+    fn as_ptr(&self) -> Tagged<O> {
         self.ptr
     }
 }
 
-impl<T, C: Cs> Pointer<T> for Snapshot<T, C> {
+impl<T, C: Cs, O: CountedObject<Target = T>> Pointer<T, O> for Snapshot<T, C, O> {
     #[inline]
-    fn as_ptr(&self) -> TaggedCnt<T> {
+    fn as_ptr(&self) -> Tagged<O> {
         self.acquired.as_ptr()
     }
 }
 
-impl<T, C: Cs> Pointer<T> for &Snapshot<T, C> {
+impl<T, C: Cs, O: CountedObject<Target = T>> Pointer<T, O> for &Snapshot<T, C, O> {
     #[inline]
-    fn as_ptr(&self) -> TaggedCnt<T> {
+    fn as_ptr(&self) -> Tagged<O> {
         self.acquired.as_ptr()
     }
 }
 
-impl<'s, T, C: Cs> Pointer<T> for TaggedSnapshot<'s, T, C> {
+impl<'s, T, C: Cs, O: CountedObject<Target = T>> Pointer<T, O> for TaggedSnapshot<'s, T, C, O> {
     #[inline]
-    fn as_ptr(&self) -> TaggedCnt<T> {
+    fn as_ptr(&self) -> Tagged<O> {
         self.inner.acquired.as_ptr().with_tag(self.tag)
     }
 }
 
-pub trait StrongPtr<T, C: Cs>: Pointer<T> {
+pub trait StrongPtr<T, C: Cs, O: CountedObject<Target = T> = Counted<T>>: Pointer<T, O> {
     const OWNS_REF_COUNT: bool;
 
     /// Consumes the aquired pointer, incrementing the reference count if we didn't increment
@@ -520,7 +570,7 @@ pub trait StrongPtr<T, C: Cs>: Pointer<T> {
     ///
     /// If `self` is already [`Rc`], it will not touch the reference count.
     #[inline]
-    fn into_rc(self) -> Rc<T, C>
+    fn into_rc(self) -> Rc<T, C, O>
     where
         Self: Sized,
     {
@@ -535,12 +585,12 @@ pub trait StrongPtr<T, C: Cs>: Pointer<T> {
 
     #[inline]
     unsafe fn deref<'g>(&self) -> &'g T {
-        self.as_ptr().deref().data()
+        &*(self.as_ptr().deref().data() as *const T)
     }
 
     #[inline]
     unsafe fn deref_mut<'g>(&mut self) -> &'g mut T {
-        self.as_ptr().deref_mut().data_mut()
+        &mut *(self.as_ptr().deref_mut().data_mut() as *mut T)
     }
 
     #[inline]
@@ -562,18 +612,20 @@ pub trait StrongPtr<T, C: Cs>: Pointer<T> {
     }
 }
 
-impl<T, C: Cs> StrongPtr<T, C> for Rc<T, C> {
+impl<T, C: Cs, O: CountedObject<Target = T>> StrongPtr<T, C, O> for Rc<T, C, O> {
     const OWNS_REF_COUNT: bool = true;
 }
 
-impl<T, C: Cs> StrongPtr<T, C> for Snapshot<T, C> {
+impl<T, C: Cs, O: CountedObject<Target = T>> StrongPtr<T, C, O> for Snapshot<T, C, O> {
     const OWNS_REF_COUNT: bool = false;
 }
 
-impl<T, C: Cs> StrongPtr<T, C> for &Snapshot<T, C> {
+impl<T, C: Cs, O: CountedObject<Target = T>> StrongPtr<T, C, O> for &Snapshot<T, C, O> {
     const OWNS_REF_COUNT: bool = false;
 }
 
-impl<'s, T, C: Cs> StrongPtr<T, C> for TaggedSnapshot<'s, T, C> {
+impl<'s, T, C: Cs, O: CountedObject<Target = T>> StrongPtr<T, C, O>
+    for TaggedSnapshot<'s, T, C, O>
+{
     const OWNS_REF_COUNT: bool = false;
 }
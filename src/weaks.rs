@@ -7,25 +7,29 @@ use std::{
 use atomic::{Atomic, Ordering};
 use static_assertions::const_assert;
 
-use crate::{Cs, Pointer, Rc, Snapshot, StrongPtr, Tagged, TaggedCnt, TaggedSnapshot};
+use crate::internal::AssertSend;
+use crate::{
+    CountValue, Counted, Cs, Pointer, Rc, Snapshot, StrongPtr, Tagged, TaggedCnt, TaggedSnapshot,
+    WeakCountedObject,
+};
 
 /// A result of unsuccessful `compare_exchange`.
 ///
 /// It returns the ownership of [`Weak`] pointer which was given as a parameter.
-pub struct CompareExchangeErrorWeak<T, P> {
+pub struct CompareExchangeErrorWeak<T, P, O: WeakCountedObject<Target = T> = Counted<T>> {
     /// The `desired` which was given as a parameter of `compare_exchange`.
     pub desired: P,
     /// The current pointer value inside the atomic pointer.
-    pub current: TaggedCnt<T>,
+    pub current: Tagged<O>,
 }
 
-pub struct AtomicWeak<T, C: Cs> {
-    pub(crate) link: Atomic<TaggedCnt<T>>,
-    _marker: PhantomData<*const C>,
+pub struct AtomicWeak<T, C: Cs, O: WeakCountedObject<Target = T> = Counted<T>> {
+    pub(crate) link: Atomic<Tagged<O>>,
+    _marker: PhantomData<(T, *const C)>,
 }
 
-unsafe impl<T: Send + Sync, C: Cs> Send for AtomicWeak<T, C> {}
-unsafe impl<T: Send + Sync, C: Cs> Sync for AtomicWeak<T, C> {}
+unsafe impl<T: Send + Sync, C: Cs, O: WeakCountedObject<Target = T>> Send for AtomicWeak<T, C, O> {}
+unsafe impl<T: Send + Sync, C: Cs, O: WeakCountedObject<Target = T>> Sync for AtomicWeak<T, C, O> {}
 
 // Ensure that TaggedPtr<T> is 8-byte long,
 // so that lock-free atomic operations are possible.
@@ -33,7 +37,7 @@ const_assert!(Atomic::<TaggedCnt<u8>>::is_lock_free());
 const_assert!(mem::size_of::<TaggedCnt<u8>>() == mem::size_of::<usize>());
 const_assert!(mem::size_of::<Atomic<TaggedCnt<u8>>>() == mem::size_of::<AtomicUsize>());
 
-impl<T, C: Cs> AtomicWeak<T, C> {
+impl<T, C: Cs, O: WeakCountedObject<Target = T>> AtomicWeak<T, C, O> {
     #[inline(always)]
     pub fn null() -> Self {
         Self {
@@ -48,18 +52,18 @@ impl<T, C: Cs> AtomicWeak<T, C> {
     /// neither a SMR nor a reference count. To dereference, use `load_from_weak` method of
     /// [`Snapshot`] instead.
     #[inline]
-    pub fn load(&self, order: Ordering) -> TaggedCnt<T> {
+    pub fn load(&self, order: Ordering) -> Tagged<O> {
         self.link.load(order)
     }
 
     #[inline]
-    pub fn store<P: WeakPtr<T, C>>(&self, ptr: P, order: Ordering, cs: &C) {
+    pub fn store<P: WeakPtr<T, C, O>>(&self, ptr: P, order: Ordering, cs: &C) {
         let new_ptr = ptr.as_ptr();
         ptr.into_weak_count();
         let old_ptr = self.link.swap(new_ptr, order);
         unsafe {
             if let Some(cnt) = old_ptr.as_raw().as_mut() {
-                cs.delayed_decrement_weak_cnt(cnt);
+                cs.delayed_decrement_weak_cnt(AssertSend::from_mut(cnt));
             }
         }
     }
@@ -68,7 +72,7 @@ impl<T, C: Cs> AtomicWeak<T, C> {
     /// This operation is thread-safe.
     /// (It is equivalent to `exchange` from the original implementation.)
     #[inline(always)]
-    pub fn swap(&self, new: Weak<T, C>, order: Ordering, _: &C) -> Weak<T, C> {
+    pub fn swap(&self, new: Weak<T, C, O>, order: Ordering, _: &C) -> Weak<T, C, O> {
         let new_ptr = new.into_raw();
         Weak::from_raw(self.link.swap(new_ptr, order))
     }
@@ -79,14 +83,14 @@ impl<T, C: Cs> AtomicWeak<T, C> {
     #[inline(always)]
     pub fn compare_exchange<'g, P>(
         &self,
-        expected: TaggedCnt<T>,
+        expected: Tagged<O>,
         desired: P,
         success: Ordering,
         failure: Ordering,
         _: &'g C,
-    ) -> Result<Weak<T, C>, CompareExchangeErrorWeak<T, P>>
+    ) -> Result<Weak<T, C, O>, CompareExchangeErrorWeak<T, P, O>>
     where
-        P: WeakPtr<T, C>,
+        P: WeakPtr<T, C, O>,
     {
         match self
             .link
@@ -115,9 +119,9 @@ impl<T, C: Cs> AtomicWeak<T, C> {
         success: Ordering,
         failure: Ordering,
         _: &'g C,
-    ) -> Result<TaggedCnt<T>, CompareExchangeErrorWeak<T, TaggedCnt<T>>>
+    ) -> Result<Tagged<O>, CompareExchangeErrorWeak<T, Tagged<O>, O>>
     where
-        P: StrongPtr<T, C>,
+        P: StrongPtr<T, C, O>,
     {
         let desired = expected.as_ptr().with_tag(desired_tag);
         match self
@@ -130,18 +134,18 @@ impl<T, C: Cs> AtomicWeak<T, C> {
     }
 
     #[inline(always)]
-    pub fn fetch_or<'g>(&self, tag: usize, order: Ordering, _: &'g C) -> TaggedCnt<T> {
-        // HACK: The size and alignment of `Atomic<TaggedCnt<T>>` will be same with `AtomicUsize`.
+    pub fn fetch_or<'g>(&self, tag: usize, order: Ordering, _: &'g C) -> Tagged<O> {
+        // HACK: The size and alignment of `Atomic<Tagged<O>>` will be same with `AtomicUsize`.
         // The equality of the sizes is checked by `const_assert!`.
         let link = unsafe { &*(&self.link as *const _ as *const AtomicUsize) };
         let prev = link.fetch_or(tag, order);
-        TaggedCnt::new(prev as *mut _)
+        Tagged::new(prev as *mut _)
     }
 }
 
-impl<T, C: Cs> From<Weak<T, C>> for AtomicWeak<T, C> {
+impl<T, C: Cs, O: WeakCountedObject<Target = T>> From<Weak<T, C, O>> for AtomicWeak<T, C, O> {
     #[inline]
-    fn from(value: Weak<T, C>) -> Self {
+    fn from(value: Weak<T, C, O>) -> Self {
         let init_ptr = value.into_raw();
         Self {
             link: Atomic::new(init_ptr),
@@ -150,42 +154,45 @@ impl<T, C: Cs> From<Weak<T, C>> for AtomicWeak<T, C> {
     }
 }
 
-impl<T, C: Cs> Drop for AtomicWeak<T, C> {
+impl<T, C: Cs, O: WeakCountedObject<Target = T>> Drop for AtomicWeak<T, C, O> {
     #[inline(always)]
     fn drop(&mut self) {
         let ptr = self.link.load(Ordering::SeqCst);
         unsafe {
             if let Some(cnt) = ptr.as_raw().as_mut() {
                 let cs = C::new();
-                cs.delayed_decrement_weak_cnt(cnt);
+                cs.release_link_weak_cnt(
+                    AssertSend::from_link(&self.link),
+                    AssertSend::from_mut(cnt),
+                );
             }
         }
     }
 }
 
-impl<T, C: Cs> Default for AtomicWeak<T, C> {
+impl<T, C: Cs, O: WeakCountedObject<Target = T>> Default for AtomicWeak<T, C, O> {
     #[inline(always)]
     fn default() -> Self {
         Self::null()
     }
 }
 
-pub struct Weak<T, C: Cs> {
-    ptr: TaggedCnt<T>,
-    _marker: PhantomData<*const C>,
+pub struct Weak<T, C: Cs, O: WeakCountedObject<Target = T> = Counted<T>> {
+    ptr: Tagged<O>,
+    _marker: PhantomData<(T, *const C)>,
 }
 
-unsafe impl<T: Send + Sync, C: Cs> Send for Weak<T, C> {}
-unsafe impl<T: Send + Sync, C: Cs> Sync for Weak<T, C> {}
+unsafe impl<T: Send + Sync, C: Cs, O: WeakCountedObject<Target = T>> Send for Weak<T, C, O> {}
+unsafe impl<T: Send + Sync, C: Cs, O: WeakCountedObject<Target = T>> Sync for Weak<T, C, O> {}
 
-impl<T, C: Cs> Weak<T, C> {
+impl<T, C: Cs, O: WeakCountedObject<Target = T>> Weak<T, C, O> {
     #[inline(always)]
     pub fn null() -> Self {
-        Self::from_raw(TaggedCnt::null())
+        Self::from_raw(Tagged::null())
     }
 
     #[inline(always)]
-    pub(crate) fn from_raw(ptr: TaggedCnt<T>) -> Self {
+    pub(crate) fn from_raw(ptr: Tagged<O>) -> Self {
         Self {
             ptr,
             _marker: PhantomData,
@@ -195,7 +202,7 @@ impl<T, C: Cs> Weak<T, C> {
     #[inline(always)]
     pub fn from_strong<'g, P>(ptr: &P, cs: &'g C) -> Self
     where
-        P: StrongPtr<T, C>,
+        P: StrongPtr<T, C, O>,
     {
         let weak = Self {
             ptr: ptr.as_ptr(),
@@ -229,7 +236,7 @@ impl<T, C: Cs> Weak<T, C> {
     }
 
     #[inline]
-    pub fn upgrade(&self, cs: &C) -> Rc<T, C> {
+    pub fn upgrade(&self, cs: &C) -> Rc<T, C, O> {
         unsafe {
             if let Some(cnt) = self.ptr.as_raw().as_ref() {
                 if cs.increment_ref_cnt(cnt) {
@@ -241,12 +248,12 @@ impl<T, C: Cs> Weak<T, C> {
     }
 
     #[inline(always)]
-    pub fn ref_count(&self) -> u32 {
+    pub fn ref_count(&self) -> CountValue {
         unsafe { self.ptr.deref().ref_count() }
     }
 
     #[inline(always)]
-    pub fn weak_count(&self) -> u32 {
+    pub fn weak_count(&self) -> CountValue {
         unsafe { self.ptr.deref().weak_count() }
     }
 
@@ -257,7 +264,7 @@ impl<T, C: Cs> Weak<T, C> {
 
     #[inline(always)]
     pub fn untagged(mut self) -> Self {
-        self.ptr = TaggedCnt::new(self.ptr.as_raw());
+        self.ptr = Tagged::new(self.ptr.as_raw());
         self
     }
 
@@ -268,7 +275,7 @@ impl<T, C: Cs> Weak<T, C> {
     }
 
     #[inline]
-    pub(crate) fn into_raw(self) -> TaggedCnt<T> {
+    pub(crate) fn into_raw(self) -> Tagged<O> {
         let new_ptr = self.as_ptr();
         // Skip decrementing the ref count.
         forget(self);
@@ -276,33 +283,33 @@ impl<T, C: Cs> Weak<T, C> {
     }
 }
 
-impl<T, C: Cs> Drop for Weak<T, C> {
+impl<T, C: Cs, O: WeakCountedObject<Target = T>> Drop for Weak<T, C, O> {
     #[inline(always)]
     fn drop(&mut self) {
         unsafe {
             if let Some(cnt) = self.ptr.as_raw().as_mut() {
                 let cs = C::new();
-                cs.delayed_decrement_weak_cnt(cnt);
+                cs.delayed_decrement_weak_cnt(AssertSend::from_mut(cnt));
             }
         }
     }
 }
 
-impl<T, C: Cs> PartialEq for Weak<T, C> {
+impl<T, C: Cs, O: WeakCountedObject<Target = T>> PartialEq for Weak<T, C, O> {
     #[inline(always)]
     fn eq(&self, other: &Self) -> bool {
         self.ptr == other.ptr
     }
 }
 
-impl<T, C: Cs> Pointer<T> for Weak<T, C> {
+impl<T, C: Cs, O: WeakCountedObject<Target = T>> Pointer<T, O> for Weak<T, C, O> {
     #[inline]
-    fn as_ptr(&self) -> TaggedCnt<T> {
+    fn as_ptr(&self) -> Tagged<O> {
         self.ptr
     }
 }
 
-pub trait WeakPtr<T, G>: Pointer<T> {
+pub trait WeakPtr<T, G, O = Counted<T>>: Pointer<T, O> {
     /// Consumes the aquired pointer, incrementing the reference count if we didn't increment
     /// it before.
     ///
@@ -314,7 +321,7 @@ pub trait WeakPtr<T, G>: Pointer<T> {
     fn into_weak_count(self);
 }
 
-impl<T, C: Cs> WeakPtr<T, C> for Weak<T, C> {
+impl<T, C: Cs, O: WeakCountedObject<Target = T>> WeakPtr<T, C, O> for Weak<T, C, O> {
     #[inline]
     fn into_weak_count(self) {
         // As we have a reference count already, we don't have to do anything, but
@@ -323,7 +330,7 @@ impl<T, C: Cs> WeakPtr<T, C> for Weak<T, C> {
     }
 }
 
-impl<T, C: Cs> WeakPtr<T, C> for Snapshot<T, C> {
+impl<T, C: Cs, O: WeakCountedObject<Target = T>> WeakPtr<T, C, O> for Snapshot<T, C, O> {
     #[inline]
     fn into_weak_count(self) {
         if let Some(cnt) = unsafe { self.as_ptr().as_raw().as_ref() } {
@@ -332,7 +339,7 @@ impl<T, C: Cs> WeakPtr<T, C> for Snapshot<T, C> {
     }
 }
 
-impl<T, C: Cs> WeakPtr<T, C> for &Snapshot<T, C> {
+impl<T, C: Cs, O: WeakCountedObject<Target = T>> WeakPtr<T, C, O> for &Snapshot<T, C, O> {
     #[inline]
     fn into_weak_count(self) {
         if let Some(cnt) = unsafe { self.as_ptr().as_raw().as_ref() } {
@@ -341,7 +348,9 @@ impl<T, C: Cs> WeakPtr<T, C> for &Snapshot<T, C> {
     }
 }
 
-impl<'s, T, C: Cs> WeakPtr<T, C> for TaggedSnapshot<'s, T, C> {
+impl<'s, T, C: Cs, O: WeakCountedObject<Target = T>> WeakPtr<T, C, O>
+    for TaggedSnapshot<'s, T, C, O>
+{
     #[inline]
     fn into_weak_count(self) {
         if let Some(cnt) = unsafe { self.as_ptr().as_raw().as_ref() } {
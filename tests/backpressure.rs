@@ -0,0 +1,96 @@
+use cdrc_rs::{ebr_impl, hp_impl, Backpressure, Cs, GarbageLimit, Rc, Stats};
+
+use std::thread;
+use std::time::Duration;
+
+const HIGH_WATER: usize = 1024;
+const LOW_WATER: usize = 256;
+
+/// A collection interval long enough to keep the default backends from reclaiming by themselves.
+const RARELY: usize = 1 << 30;
+
+fn configure_ebr(limit: Option<GarbageLimit>) {
+    let collector = ebr_impl::default_collector();
+    let config = ebr_impl::Config::builder()
+        .collect_interval(RARELY)
+        .advance_interval(RARELY)
+        .garbage_limit(limit)
+        .build()
+        .unwrap();
+    collector.set_config(config);
+}
+
+fn configure_hp(limit: Option<GarbageLimit>) {
+    let config = hp_impl::Config::builder()
+        .collect_interval(RARELY)
+        .garbage_limit(limit)
+        .build()
+        .unwrap();
+    hp_impl::DEFAULT_DOMAIN.set_config(config);
+}
+
+/// Drops objects in a new thread, and returns the maximum number of unreclaimed objects seen.
+fn churn<C: Cs>(count: usize, stats: fn() -> Stats) -> u64 {
+    thread::spawn(move || {
+        let mut max_unreclaimed = 0;
+        for i in 0..count {
+            drop(Rc::<_, C>::new(i));
+            if i % 256 == 0 {
+                max_unreclaimed = max_unreclaimed.max(stats().unreclaimed);
+            }
+        }
+        max_unreclaimed
+    })
+    .join()
+    .unwrap()
+}
+
+/// Drops many objects under a garbage limit while the backend rarely reclaims by itself, and
+/// checks that the number of unreclaimed objects stays around the high-water mark.
+fn check<C: Cs>(configure: fn(Option<GarbageLimit>), stats: fn() -> Stats) {
+    const COUNT: usize = 100_000;
+
+    for backpressure in [
+        Backpressure::Collect,
+        Backpressure::Block(Duration::from_millis(1)),
+    ] {
+        let limit = GarbageLimit::new(HIGH_WATER, LOW_WATER, backpressure).unwrap();
+        configure(Some(limit));
+        let max_unreclaimed = churn::<C>(COUNT, stats);
+
+        // Up to a bag or a batch of retired objects may be held by the thread itself.
+        assert!(
+            max_unreclaimed <= 2 * HIGH_WATER as u64,
+            "{} unreclaimed objects with {:?}",
+            max_unreclaimed,
+            backpressure
+        );
+    }
+
+    configure(None);
+    let unbounded = churn::<C>(COUNT, stats);
+    assert!(unbounded > 2 * HIGH_WATER as u64);
+
+    // Reclaim the garbage left by the unbounded run.
+    let mut cs = C::new();
+    for _ in 0..16 {
+        cs.eager_reclaim();
+    }
+}
+
+#[test]
+fn smoke_ebr() {
+    check::<cdrc_rs::CsEBR>(configure_ebr, cdrc_rs::stats_ebr);
+}
+
+#[test]
+fn smoke_hp() {
+    check::<cdrc_rs::CsHP>(configure_hp, cdrc_rs::stats_hp);
+}
+
+#[test]
+fn invalid_limit() {
+    assert!(GarbageLimit::new(0, 0, Backpressure::Collect).is_err());
+    assert!(GarbageLimit::new(16, 32, Backpressure::Collect).is_err());
+    assert!(GarbageLimit::new(16, 16, Backpressure::Collect).is_ok());
+}
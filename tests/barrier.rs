@@ -0,0 +1,143 @@
+use atomic::Ordering;
+use cdrc_rs::{AtomicRc, Cs, Rc, Snapshot, StrongPtr};
+
+use std::sync::atomic::{AtomicBool, AtomicUsize};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+
+struct Node<C: Cs> {
+    next: AtomicRc<Node<C>, C>,
+    drops: Arc<AtomicUsize>,
+}
+
+impl<C: Cs> Drop for Node<C> {
+    fn drop(&mut self) {
+        self.drops.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Builds and drops chains of nodes in several threads, and checks that a barrier reclaims all of
+/// them, including the ones retired by ejecting the others.
+fn check_barrier<C: Cs + 'static>() {
+    const THREADS: usize = 4;
+    const COUNT: usize = 1000;
+
+    let drops = Arc::new(AtomicUsize::new(0));
+    thread::scope(|s| {
+        for _ in 0..THREADS {
+            s.spawn(|| {
+                let mut head = Rc::<Node<C>, C>::null();
+                for _ in 0..COUNT {
+                    head = Rc::new(Node {
+                        next: AtomicRc::from(head),
+                        drops: drops.clone(),
+                    });
+                }
+
+                let cs = C::new();
+                let mut node = Snapshot::new();
+                node.protect(&head, &cs);
+                let mut len = 0;
+                while let Some(curr) = node.as_ref() {
+                    let mut next = Snapshot::new();
+                    next.load(&curr.next, &cs);
+                    node = next;
+                    len += 1;
+                }
+                assert_eq!(len, COUNT);
+            });
+        }
+    });
+
+    // The current thread also retires objects, which it keeps in its own buffer.
+    drop(Rc::<Node<C>, C>::new(Node {
+        next: AtomicRc::null(),
+        drops: drops.clone(),
+    }));
+
+    cdrc_rs::barrier::<C>();
+    assert_eq!(drops.load(Ordering::Relaxed), THREADS * COUNT + 1);
+}
+
+/// Checks that `synchronize` waits for a snapshot taken by another thread.
+fn check_synchronize<C: Cs + 'static>() {
+    let (protected_tx, protected_rx) = mpsc::channel();
+    let (release_tx, release_rx) = mpsc::channel::<()>();
+    let holder = thread::spawn(move || {
+        let atomic = AtomicRc::<usize, C>::new(42);
+        let cs = C::new();
+        let mut snapshot = Snapshot::new();
+        snapshot.load(&atomic, &cs);
+        protected_tx.send(()).unwrap();
+        release_rx.recv().unwrap();
+        drop(snapshot);
+        drop(cs);
+    });
+    protected_rx.recv().unwrap();
+
+    let synchronized = Arc::new(AtomicBool::new(false));
+    let waiter = {
+        let synchronized = synchronized.clone();
+        thread::spawn(move || {
+            cdrc_rs::synchronize::<C>();
+            synchronized.store(true, Ordering::Release);
+        })
+    };
+    thread::sleep(Duration::from_millis(50));
+    assert!(!synchronized.load(Ordering::Acquire));
+
+    release_tx.send(()).unwrap();
+    waiter.join().unwrap();
+    assert!(synchronized.load(Ordering::Acquire));
+    holder.join().unwrap();
+}
+
+/// Checks that `try_barrier` gives up while an idle thread keeps its retired objects, and succeeds
+/// once the thread exits.
+fn check_try_barrier<C: Cs + 'static>() {
+    let drops = Arc::new(AtomicUsize::new(0));
+    let (retired_tx, retired_rx) = mpsc::channel();
+    let (exit_tx, exit_rx) = mpsc::channel::<()>();
+    let idle = {
+        let drops = drops.clone();
+        thread::spawn(move || {
+            drop(Rc::<Node<C>, C>::new(Node {
+                next: AtomicRc::null(),
+                drops,
+            }));
+            retired_tx.send(()).unwrap();
+            exit_rx.recv().unwrap();
+        })
+    };
+    retired_rx.recv().unwrap();
+
+    assert!(!cdrc_rs::try_barrier::<C>(Duration::from_millis(50)));
+    assert_eq!(drops.load(Ordering::Relaxed), 0);
+
+    exit_tx.send(()).unwrap();
+    idle.join().unwrap();
+    assert!(cdrc_rs::try_barrier::<C>(Duration::from_secs(10)));
+    assert_eq!(drops.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn smoke_ebr() {
+    check_barrier::<cdrc_rs::CsEBR>();
+    check_synchronize::<cdrc_rs::CsEBR>();
+    check_try_barrier::<cdrc_rs::CsEBR>();
+}
+
+#[test]
+fn smoke_hp() {
+    check_barrier::<cdrc_rs::CsHP>();
+    check_synchronize::<cdrc_rs::CsHP>();
+    check_try_barrier::<cdrc_rs::CsHP>();
+}
+
+#[test]
+#[should_panic(expected = "called while pinned")]
+fn synchronize_in_critical_section() {
+    let _cs = cdrc_rs::CsEBR::new();
+    cdrc_rs::synchronize::<cdrc_rs::CsEBR>();
+}
@@ -0,0 +1,78 @@
+use cdrc_rs::{ebr_impl, hp_impl, ReclaimBudget};
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+const COUNT: usize = 1000;
+const BUDGET: usize = 16;
+
+/// A collection interval long enough to keep deferrals from reclaiming by themselves.
+const RARELY: usize = 1 << 30;
+
+/// Calls `reclaim` until all objects are reclaimed, and checks that each call reclaims at most
+/// `BUDGET` of them.
+fn drain(reclaimed: &AtomicUsize, mut reclaim: impl FnMut()) {
+    let mut calls = 0;
+    while reclaimed.load(Ordering::Relaxed) < COUNT {
+        let before = reclaimed.load(Ordering::Relaxed);
+        reclaim();
+        let after = reclaimed.load(Ordering::Relaxed);
+        assert!(
+            after - before <= BUDGET,
+            "{} objects in a call",
+            after - before
+        );
+        calls += 1;
+        assert!(calls < 100 * COUNT, "reclamation got stuck");
+    }
+    assert!(calls >= COUNT / BUDGET);
+}
+
+#[test]
+fn smoke_ebr() {
+    let config = ebr_impl::Config::builder()
+        .collect_interval(RARELY)
+        .advance_interval(RARELY)
+        .reclaim_budget(Some(ReclaimBudget::Objects(BUDGET)))
+        .build()
+        .unwrap();
+    let collector = ebr_impl::Collector::with_config(config);
+    let handle = collector.register();
+    let reclaimed = Arc::new(AtomicUsize::new(0));
+
+    for _ in 0..COUNT {
+        let reclaimed = reclaimed.clone();
+        handle
+            .pin()
+            .defer(move || reclaimed.fetch_add(1, Ordering::Relaxed));
+    }
+    drain(&reclaimed, || handle.pin().flush());
+}
+
+#[test]
+fn smoke_hp() {
+    static DOMAIN: hp_impl::Domain = hp_impl::Domain::new();
+
+    struct Tracked(Arc<AtomicUsize>);
+
+    impl Drop for Tracked {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    let config = hp_impl::Config::builder()
+        .collect_interval(RARELY)
+        .reclaim_budget(Some(ReclaimBudget::Objects(BUDGET)))
+        .build()
+        .unwrap();
+    DOMAIN.set_config(config);
+    let thread = hp_impl::Thread::new(&DOMAIN);
+    let reclaimed = Arc::new(AtomicUsize::new(0));
+
+    for _ in 0..COUNT {
+        let tracked = Box::new(Tracked(reclaimed.clone()));
+        unsafe { thread.retire(Box::into_raw(tracked)) };
+    }
+    drain(&reclaimed, || thread.eager_reclaim());
+}
@@ -0,0 +1,104 @@
+use atomic::Ordering;
+use cdrc_rs::hp_impl::Cohort;
+use cdrc_rs::{AtomicRc, Cs, CsHP, Rc, Snapshot};
+
+use std::panic;
+use std::sync::atomic::{AtomicBool, AtomicUsize};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Tracked;
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[test]
+fn drop_waits_for_protection() {
+    let root = AtomicRc::<Tracked, CsHP>::new(Tracked);
+    let cohort = Cohort::with_drop_timeout(Duration::from_secs(60));
+    let (protected_tx, protected_rx) = mpsc::channel();
+    let (release_tx, release_rx) = mpsc::channel::<()>();
+    let dropped_cohort = AtomicBool::new(false);
+
+    thread::scope(|scope| {
+        let root = &root;
+        scope.spawn(move || {
+            let cs = CsHP::new();
+            let mut snapshot = Snapshot::new();
+            snapshot.load(root, &cs);
+            protected_tx.send(()).unwrap();
+            release_rx.recv().unwrap();
+        });
+        protected_rx.recv().unwrap();
+
+        cohort.scope(|| root.store(Rc::null(), Ordering::Release, &CsHP::new()));
+        assert_eq!(cohort.len(), 1);
+
+        let dropper = scope.spawn(|| {
+            drop(cohort);
+            dropped_cohort.store(true, Ordering::Release);
+        });
+        thread::sleep(Duration::from_millis(50));
+        assert!(!dropped_cohort.load(Ordering::Acquire));
+        assert_eq!(DROPPED.load(Ordering::Relaxed), 0);
+
+        release_tx.send(()).unwrap();
+        dropper.join().unwrap();
+    });
+    assert_eq!(DROPPED.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn scope_restores_on_panic() {
+    let cohort = Cohort::new();
+    let root = AtomicRc::<usize, CsHP>::new(0);
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        cohort.scope(|| {
+            root.store(Rc::new(1), Ordering::Release, &CsHP::new());
+            panic!("left the scope");
+        })
+    }));
+    assert!(result.is_err());
+    assert_eq!(cohort.len(), 1);
+
+    // The thread has left the cohort, so this retires to the domain.
+    root.store(Rc::null(), Ordering::Release, &CsHP::new());
+    assert_eq!(cohort.len(), 1);
+}
+
+#[test]
+fn drop_hands_protected_over_to_domain() {
+    static HANDED_OVER: AtomicUsize = AtomicUsize::new(0);
+
+    struct HandedOver;
+
+    impl Drop for HandedOver {
+        fn drop(&mut self) {
+            HANDED_OVER.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    let root = AtomicRc::<HandedOver, CsHP>::new(HandedOver);
+    let cohort = Cohort::new();
+    let cs = CsHP::new();
+    let mut snapshot = Snapshot::new();
+    snapshot.load(&root, &cs);
+
+    cohort.scope(|| root.store(Rc::null(), Ordering::Release, &CsHP::new()));
+    assert_eq!(cohort.len(), 1);
+
+    // The dropping thread itself protects the object, so the drop hands it over right away.
+    drop(cohort);
+    assert_eq!(HANDED_OVER.load(Ordering::Relaxed), 0);
+
+    drop(snapshot);
+    drop(cs);
+    CsHP::barrier();
+    assert_eq!(HANDED_OVER.load(Ordering::Relaxed), 1);
+}
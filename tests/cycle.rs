@@ -0,0 +1,109 @@
+#![cfg(feature = "cycle-detector")]
+
+use atomic::Ordering;
+use cdrc_rs::{AtomicRc, AtomicWeak, Cs, CycleDetector, Rc, StrongPtr, Trace, Tracer, Weak};
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::Arc;
+
+struct Node<C: Cs> {
+    next: AtomicRc<Node<C>, C>,
+    side: AtomicRc<Node<C>, C>,
+    prev: AtomicWeak<Node<C>, C>,
+    drops: Arc<AtomicUsize>,
+}
+
+impl<C: Cs> Drop for Node<C> {
+    fn drop(&mut self) {
+        self.drops.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl<C: Cs> Trace<C> for Node<C> {
+    fn trace(&self, tracer: &mut Tracer<'_, C>) {
+        tracer.visit_atomic(&self.next);
+        tracer.visit_atomic(&self.side);
+    }
+}
+
+fn node<C: Cs>(drops: &Arc<AtomicUsize>) -> Rc<Node<C>, C> {
+    Rc::new(Node {
+        next: AtomicRc::null(),
+        side: AtomicRc::null(),
+        prev: AtomicWeak::null(),
+        drops: drops.clone(),
+    })
+}
+
+/// Builds a ring of `len` nodes, whose `prev` links are weak, and returns its first node.
+fn ring<C: Cs>(
+    len: usize,
+    drops: &Arc<AtomicUsize>,
+    detector: &mut CycleDetector<C>,
+) -> Rc<Node<C>, C> {
+    let cs = &C::new();
+    let first = node(drops);
+    let mut last = first.clone(cs);
+    for _ in 1..len {
+        let node = node(drops);
+        let last_ref = unsafe { last.deref() };
+        unsafe { node.deref() }
+            .prev
+            .store(Weak::from_strong(&last, cs), Ordering::Relaxed, cs);
+        last_ref.next.store(node.clone(cs), Ordering::Relaxed, cs);
+        detector.add(&node);
+        last = node;
+    }
+    unsafe { last.deref() }
+        .next
+        .store(first.clone(cs), Ordering::Relaxed, cs);
+    detector.add(&first);
+    first
+}
+
+fn check_cycles<C: Cs>() {
+    const LEN: usize = 3;
+
+    let drops = Arc::new(AtomicUsize::new(0));
+    let mut detector = CycleDetector::<C>::new();
+
+    // A ring which is still referenced from outside is alive.
+    let first = ring(LEN, &drops, &mut detector);
+    assert_eq!(detector.len(), LEN);
+    assert!(detector.detect().is_empty());
+
+    // A chain hanging off the ring is garbage as well, but not a cycle.
+    {
+        let cs = &C::new();
+        let tail = node(&drops);
+        unsafe { tail.deref() }
+            .next
+            .store(node(&drops), Ordering::Relaxed, cs);
+        unsafe { first.deref() }
+            .side
+            .store(tail, Ordering::Relaxed, cs);
+    }
+
+    // Once the outside reference is dropped, the ring is leaked.
+    drop(first);
+    let cycles = detector.detect();
+    assert_eq!(cycles.len(), 1, "{:?}", cycles);
+    assert_eq!(cycles[0].members.len(), LEN);
+    assert!(cycles[0].members.iter().all(|member| member.strong == 1));
+    assert!(cycles[0].to_string().starts_with("cycle of 3 objects:"));
+
+    // Collecting breaks the ring, so that the nodes are reclaimed.
+    assert_eq!(detector.collect().len(), 1);
+    cdrc_rs::barrier::<C>();
+    assert_eq!(drops.load(Ordering::Relaxed), LEN + 2);
+}
+
+#[test]
+fn smoke_ebr() {
+    check_cycles::<cdrc_rs::CsEBR>();
+}
+
+#[test]
+fn smoke_hp() {
+    check_cycles::<cdrc_rs::CsHP>();
+}
@@ -101,7 +101,7 @@ pub struct Update<K, V, C: Cs> {
     new_internal: AtomicWeak<Node<K, V, C>, C>,
 }
 
-impl<K, V, C: Cs> Node<K, V, C> {
+impl<K: Send + Sync, V: Send + Sync, C: Cs> Node<K, V, C> {
     pub fn internal(key: Key<K>, value: Option<V>, left: Self, right: Self) -> Self {
         Self {
             key,
@@ -146,8 +146,8 @@ pub struct Finder<K, V, C: Cs> {
 
 impl<K, V, C> Finder<K, V, C>
 where
-    K: Ord + Clone,
-    V: Clone,
+    K: Ord + Clone + Send + Sync,
+    V: Clone + Send + Sync,
     C: Cs,
 {
     fn new() -> Self {
@@ -245,8 +245,8 @@ pub struct EFRBTree<K, V, C: Cs> {
 
 impl<K, V, C> EFRBTree<K, V, C>
 where
-    K: Ord + Clone,
-    V: Clone,
+    K: Ord + Clone + Send + Sync,
+    V: Clone + Send + Sync,
     C: Cs,
 {
     pub fn new() -> Self {
@@ -502,7 +502,8 @@ fn smoke<C: Cs>() {
     const THREADS: i32 = 30;
     const ELEMENTS_PER_THREADS: i32 = 1000;
 
-    let map = &{ EFRBTree::new() };
+    let tree = EFRBTree::new();
+    let map = &tree;
 
     thread::scope(|s| {
         for t in 0..THREADS {
@@ -563,6 +564,13 @@ fn smoke<C: Cs>() {
         }
     })
     .unwrap();
+
+    drop(tree);
+    #[cfg(feature = "leak-check")]
+    {
+        cdrc_rs::assert_no_leaks_of::<C, Node<i32, String, C>>();
+        cdrc_rs::assert_no_leaks_of::<C, Update<i32, String, C>>();
+    }
 }
 
 #[test]
@@ -0,0 +1,106 @@
+use atomic::Ordering;
+use cdrc_rs::{AtomicRc, AtomicWeak, Count, Cs, IntrusiveCounted, Rc, Snapshot, StrongPtr, Weak};
+
+use std::mem::{swap, ManuallyDrop};
+
+/// A node which carries its own header, aligned to a cache line.
+#[repr(C, align(64))]
+struct Node<C: Cs> {
+    ref_cnt: Count,
+    weak_cnt: Count,
+    next: ManuallyDrop<AtomicRc<Node<C>, C, Node<C>>>,
+    prev: ManuallyDrop<AtomicWeak<Node<C>, C, Node<C>>>,
+    value: ManuallyDrop<String>,
+}
+
+unsafe impl<C: Cs> IntrusiveCounted for Node<C> {
+    fn ref_cnt(&self) -> &Count {
+        &self.ref_cnt
+    }
+
+    fn weak_cnt(&self) -> &Count {
+        &self.weak_cnt
+    }
+
+    unsafe fn dispose(&mut self) {
+        ManuallyDrop::drop(&mut self.next);
+        ManuallyDrop::drop(&mut self.prev);
+        ManuallyDrop::drop(&mut self.value);
+    }
+}
+
+impl<C: Cs> Node<C> {
+    fn new(value: String) -> Self {
+        Self {
+            ref_cnt: Count::new(),
+            weak_cnt: Count::new(),
+            next: ManuallyDrop::new(AtomicRc::null()),
+            prev: ManuallyDrop::new(AtomicWeak::null()),
+            value: ManuallyDrop::new(value),
+        }
+    }
+}
+
+fn smoke<C: Cs>() {
+    use crossbeam_utils::thread;
+
+    const THREADS: usize = 16;
+    const ELEMENTS: usize = 1000;
+
+    let head = &AtomicRc::<Node<C>, C, Node<C>>::null();
+
+    {
+        let cs = &C::new();
+        let mut old = Snapshot::new();
+        for i in 0..ELEMENTS {
+            let node = Rc::new(Node::new(i.to_string()));
+            old.load(head, cs);
+            unsafe { node.deref() }
+                .next
+                .store(&old, Ordering::Relaxed, cs);
+            if let Some(old) = old.as_ref() {
+                old.prev
+                    .store(Weak::from_strong(&node, cs), Ordering::Relaxed, cs);
+            }
+            head.store(node, Ordering::Release, cs);
+        }
+    }
+
+    thread::scope(|s| {
+        for _ in 0..THREADS {
+            s.spawn(move |_| {
+                let cs = &mut C::new();
+                let mut curr = Snapshot::new();
+                let mut next = Snapshot::new();
+                let mut prev = Snapshot::new();
+                let mut expected = ELEMENTS;
+                curr.load(head, cs);
+                while let Some(node) = curr.as_ref() {
+                    expected -= 1;
+                    assert_eq!(*node.value, expected.to_string());
+                    next.load(&node.next, cs);
+                    if let Some(next_node) = next.as_ref() {
+                        // The list is still reachable from `head`, so the weak link is alive.
+                        assert!(prev.load_from_weak(&next_node.prev, cs));
+                        assert!(prev == curr);
+                    }
+                    swap(&mut curr, &mut next);
+                }
+                assert_eq!(expected, 0);
+            });
+        }
+    })
+    .unwrap();
+
+    head.store(Rc::null(), Ordering::Release, &C::new());
+}
+
+#[test]
+fn smoke_ebr() {
+    smoke::<cdrc_rs::CsEBR>();
+}
+
+#[test]
+fn smoke_hp() {
+    smoke::<cdrc_rs::CsHP>();
+}
@@ -0,0 +1,61 @@
+#![cfg(feature = "leak-check")]
+
+use cdrc_rs::{
+    assert_no_leaks_of, live_counts, live_objects, AtomicRc, Cs, Pointer, Rc, StrongPtr,
+};
+
+use std::any::type_name;
+use std::mem;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::Ordering;
+
+struct Node<C: Cs> {
+    next: AtomicRc<Node<C>, C>,
+}
+
+/// Builds a chain of nodes and drops it, then leaks a node on purpose, and checks that only the
+/// leaked node is reported.
+fn check_leaks<C: Cs + 'static>() {
+    const COUNT: usize = 100;
+
+    let mut head = Rc::<Node<C>, C>::null();
+    for _ in 0..COUNT {
+        head = Rc::new(Node {
+            next: AtomicRc::from(head),
+        });
+    }
+    let first = head.as_ref().unwrap();
+    assert!(!first.next.load(Ordering::Relaxed).is_null());
+    let name = type_name::<Node<C>>();
+    assert_eq!(live_counts().get(name), Some(&COUNT));
+    drop(head);
+    assert_no_leaks_of::<C, Node<C>>();
+
+    let leaked = Rc::<Node<C>, C>::new(Node {
+        next: AtomicRc::null(),
+    });
+    let address = leaked.as_ptr().as_raw() as usize;
+    mem::forget(leaked);
+
+    let report = panic::catch_unwind(AssertUnwindSafe(assert_no_leaks_of::<C, Node<C>>))
+        .expect_err("a leaked node must be reported");
+    let report = report.downcast_ref::<String>().unwrap();
+    assert!(report.starts_with("1 objects leaked:"), "{}", report);
+
+    let object = live_objects()
+        .into_iter()
+        .find(|object| object.type_name == name)
+        .unwrap();
+    assert_eq!(object.address, address);
+    assert_eq!(object.strong, 1);
+}
+
+#[test]
+fn smoke_ebr() {
+    check_leaks::<cdrc_rs::CsEBR>();
+}
+
+#[test]
+fn smoke_hp() {
+    check_leaks::<cdrc_rs::CsHP>();
+}
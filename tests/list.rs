@@ -3,6 +3,7 @@ use cdrc_rs::{AtomicRc, Cs, Pointer, Rc, Snapshot, StrongPtr, TaggedCnt};
 
 use std::cmp::Ordering::{Equal, Greater, Less};
 use std::mem::swap;
+use std::sync::atomic::AtomicUsize;
 
 /// Some or executing the given expression.
 macro_rules! some_or {
@@ -26,8 +27,8 @@ struct List<K, V, C: Cs> {
 
 impl<K, V, C> Default for List<K, V, C>
 where
-    K: Ord + Default,
-    V: Default,
+    K: Ord + Default + Send + Sync,
+    V: Default + Send + Sync,
     C: Cs,
 {
     fn default() -> Self {
@@ -200,8 +201,8 @@ impl<K: Ord, V, C: Cs> Cursor<K, V, C> {
 
 impl<K, V, C> List<K, V, C>
 where
-    K: Ord + Default,
-    V: Default,
+    K: Ord + Default + Send + Sync,
+    V: Default + Send + Sync,
     C: Cs,
 {
     /// Creates a new list.
@@ -286,7 +287,8 @@ fn smoke<C: Cs>() {
     const THREADS: i32 = 30;
     const ELEMENTS_PER_THREADS: i32 = 1000;
 
-    let map = &List::new();
+    let list = List::new();
+    let map = &list;
 
     thread::scope(|s| {
         for t in 0..THREADS {
@@ -341,6 +343,10 @@ fn smoke<C: Cs>() {
         }
     })
     .unwrap();
+
+    drop(list);
+    #[cfg(feature = "leak-check")]
+    cdrc_rs::assert_no_leaks_of::<C, Node<i32, String, C>>();
 }
 
 #[test]
@@ -352,3 +358,91 @@ fn smoke_ebr() {
 fn smoke_hp() {
     smoke::<cdrc_rs::CsHP>();
 }
+
+/// A value which counts how many times it has been dropped.
+#[derive(Default)]
+struct Counter(Option<&'static AtomicUsize>);
+
+impl Drop for Counter {
+    fn drop(&mut self) {
+        if let Some(dropped) = self.0 {
+            dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Builds a list of `length` nodes and drops it.
+fn drop_long_list<C: Cs>(dropped: &'static AtomicUsize, length: usize) {
+    let list = List::<usize, Counter, C>::new();
+    {
+        let cs = &C::new();
+        let mut head = Snapshot::new();
+        head.load(&list.head, cs);
+        let head = unsafe { head.deref() };
+        for key in (0..length).rev() {
+            let node = Rc::new(Node::new(key, Counter(Some(dropped))));
+            let next = head.next.swap(Rc::null(), Ordering::Relaxed, cs);
+            drop(
+                unsafe { node.deref() }
+                    .next
+                    .swap(next, Ordering::Relaxed, cs),
+            );
+            drop(head.next.swap(node, Ordering::Relaxed, cs));
+        }
+    }
+
+    drop(list);
+}
+
+#[test]
+fn drop_long_list_ebr() {
+    const LENGTH: usize = 1_000_000;
+    static DROPPED: AtomicUsize = AtomicUsize::new(0);
+    drop_long_list::<cdrc_rs::CsEBR>(&DROPPED, LENGTH);
+    // The whole chain is destroyed iteratively, once the head node is reclaimed.
+    cdrc_rs::barrier::<cdrc_rs::CsEBR>();
+    assert_eq!(DROPPED.load(Ordering::Relaxed), LENGTH);
+}
+
+#[test]
+fn drop_long_list_hp() {
+    const LENGTH: usize = 10_000;
+    static DROPPED: AtomicUsize = AtomicUsize::new(0);
+    drop_long_list::<cdrc_rs::CsHP>(&DROPPED, LENGTH);
+    // Each node is released by a separate hazard scan, so a short list suffices.
+    let mut cs = cdrc_rs::CsHP::new();
+    for _ in 0..2 * LENGTH {
+        if DROPPED.load(Ordering::Relaxed) == LENGTH {
+            break;
+        }
+        cs.eager_reclaim();
+    }
+    assert_eq!(DROPPED.load(Ordering::Relaxed), LENGTH);
+}
+
+#[test]
+fn drop_list_in_cohort_hp() {
+    use cdrc_rs::hp_impl::Cohort;
+    use cdrc_rs::CsHP;
+
+    const LENGTH: usize = 1000;
+    static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+    let cohort = Cohort::new();
+    cohort.scope(|| {
+        let list = List::<usize, Counter, CsHP>::new();
+        let cursor = &mut Cursor::new();
+        let cs = &CsHP::new();
+        for key in (0..LENGTH).rev() {
+            assert!(list.harris_insert(key, Counter(Some(&DROPPED)), cursor, cs));
+        }
+        for key in (0..LENGTH).step_by(2) {
+            assert!(list.harris_remove(&key, cursor, cs));
+        }
+        drop(list);
+    });
+
+    // Dropping the cohort reclaims the whole list, without any other reclamation.
+    drop(cohort);
+    assert_eq!(DROPPED.load(Ordering::Relaxed), LENGTH);
+}
@@ -0,0 +1,86 @@
+use cdrc_rs::hp_impl::{Config, Domain, HazardPointer, Thread};
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+const COLLECT: usize = 32;
+
+struct Tracked(Arc<AtomicUsize>);
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+fn local_config() -> Config {
+    Config::builder()
+        .collect_interval(COLLECT)
+        .local_reclamation(true)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn push_only_protected() {
+    static DOMAIN: Domain = Domain::new();
+    DOMAIN.set_config(local_config());
+    let thread = Thread::new(&DOMAIN);
+    let drops = Arc::new(AtomicUsize::new(0));
+    let retire = || unsafe { thread.retire(Box::into_raw(Box::new(Tracked(drops.clone())))) };
+
+    let protected = Box::into_raw(Box::new(Tracked(drops.clone())));
+    let mut hp = HazardPointer::new(&thread);
+    hp.protect_raw(protected);
+    unsafe { thread.retire(protected) };
+    (1..COLLECT).for_each(|_| retire());
+    // The thread has reclaimed its own objects but the protected one, which is in the domain.
+    assert_eq!(drops.load(Ordering::Relaxed), COLLECT - 1);
+    assert_eq!(DOMAIN.num_garbages(), 1);
+
+    hp.reset_protection();
+    (0..COLLECT).for_each(|_| retire());
+    // The next scan reclaims only the objects of the thread, ...
+    assert_eq!(drops.load(Ordering::Relaxed), 2 * COLLECT - 1);
+    assert_eq!(DOMAIN.num_garbages(), 1);
+
+    // ... but the domain is drained within a few more.
+    let mut passes = 2;
+    while DOMAIN.num_garbages() > 0 {
+        assert!(passes < 16, "the domain is never drained");
+        (0..COLLECT).for_each(|_| retire());
+        passes += 1;
+    }
+    assert_eq!(drops.load(Ordering::Relaxed), passes * COLLECT);
+}
+
+#[test]
+fn concurrent() {
+    const THREADS: usize = 8;
+    const COUNT: usize = 10_000;
+    static DOMAIN: Domain = Domain::new();
+    DOMAIN.set_config(local_config());
+    let drops = Arc::new(AtomicUsize::new(0));
+
+    thread::scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|| {
+                let thread = Thread::new(&DOMAIN);
+                let mut hp = HazardPointer::new(&thread);
+                for i in 0..COUNT {
+                    let ptr = Box::into_raw(Box::new(Tracked(drops.clone())));
+                    if i % 7 == 0 {
+                        hp.protect_raw(ptr);
+                    }
+                    unsafe { thread.retire(ptr) };
+                }
+            });
+        }
+    });
+
+    // The leftovers of the exited threads are in the domain.
+    Thread::new(&DOMAIN).eager_reclaim();
+    assert_eq!(drops.load(Ordering::Relaxed), THREADS * COUNT);
+    assert_eq!(DOMAIN.num_garbages(), 0);
+}
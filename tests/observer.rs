@@ -0,0 +1,95 @@
+#![cfg(feature = "observer")]
+
+use cdrc_rs::{AtomicWeak, Cs, Observer, Rc, RetireType, Snapshot, Weak};
+
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+/// Checks that every object is disposed exactly once, and before it is destroyed.
+#[derive(Default)]
+struct Checker {
+    /// The objects disposed and not destroyed yet.
+    disposed: Mutex<HashSet<usize>>,
+    violations: AtomicUsize,
+    retires: AtomicUsize,
+    destroys: AtomicUsize,
+    advances: AtomicUsize,
+    reclamations: AtomicUsize,
+}
+
+impl Observer for Checker {
+    fn on_retire(&self, _object: *const u8, _ret_type: RetireType) {
+        self.retires.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn on_dispose(&self, object: *const u8) {
+        if !self.disposed.lock().unwrap().insert(object as usize) {
+            self.violations.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn on_destroy(&self, object: *const u8) {
+        self.destroys.fetch_add(1, Ordering::Relaxed);
+        if !self.disposed.lock().unwrap().remove(&(object as usize)) {
+            self.violations.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn on_epoch_advance(&self, _epoch: cdrc_rs::ebr_impl::Epoch) {
+        self.advances.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn on_reclamation(&self, _reclaimed: usize, _remaining: usize) {
+        self.reclamations.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Returns the checker, registering it on the first call.
+fn checker() -> &'static Checker {
+    static CHECKER: OnceLock<&'static Checker> = OnceLock::new();
+    CHECKER.get_or_init(|| {
+        let checker = Box::leak(Box::<Checker>::default());
+        cdrc_rs::set_observer(checker);
+        checker
+    })
+}
+
+/// Creates objects with and without weak pointers to them, and reclaims them.
+fn churn<C: Cs>() {
+    const COUNT: usize = 10_000;
+
+    let checker = checker();
+    let weak = AtomicWeak::<_, C>::null();
+    let cs = &mut C::new();
+    for i in 0..COUNT {
+        let strong = Rc::<_, C>::new(i);
+        if i % 2 == 0 {
+            // The weak link delays the destruction of the object after its disposal.
+            weak.store(Weak::from_strong(&strong, cs), Ordering::Release, cs);
+        }
+        drop(strong);
+        let mut snapshot = Snapshot::new();
+        snapshot.load_from_weak(&weak, cs);
+    }
+    drop(weak);
+    for _ in 0..16 {
+        cs.eager_reclaim();
+    }
+
+    assert!(checker.retires.load(Ordering::Relaxed) > 0);
+    assert!(checker.destroys.load(Ordering::Relaxed) > 0);
+    assert_eq!(checker.violations.load(Ordering::Relaxed), 0);
+}
+
+#[test]
+fn smoke_ebr() {
+    churn::<cdrc_rs::CsEBR>();
+    assert!(checker().advances.load(Ordering::Relaxed) > 0);
+}
+
+#[test]
+fn smoke_hp() {
+    churn::<cdrc_rs::CsHP>();
+    assert!(checker().reclamations.load(Ordering::Relaxed) > 0);
+}
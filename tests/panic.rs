@@ -0,0 +1,73 @@
+use cdrc_rs::{Cs, PanicPolicy, Rc};
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// Serializes the tests, as the collected panics are global.
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// An object whose destructor may panic.
+struct Bomb {
+    explode: bool,
+    dropped: &'static AtomicUsize,
+}
+
+impl Drop for Bomb {
+    fn drop(&mut self) {
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+        if self.explode {
+            panic!("boom");
+        }
+    }
+}
+
+/// Drops `count` objects, one in ten of which panics on drop, and reclaims them.
+fn drop_bombs<C: Cs>(count: usize, dropped: &'static AtomicUsize) {
+    let target = dropped.load(Ordering::Relaxed) + count;
+    for i in 0..count {
+        drop(Rc::<_, C>::new(Bomb {
+            explode: i % 10 == 0,
+            dropped,
+        }));
+    }
+
+    let mut cs = C::new();
+    for _ in 0..1024 {
+        if dropped.load(Ordering::Relaxed) == target {
+            break;
+        }
+        cs.eager_reclaim();
+    }
+    assert_eq!(dropped.load(Ordering::Relaxed), target);
+}
+
+fn panicking_destructors<C: Cs>(dropped: &'static AtomicUsize) {
+    let _lock = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    cdrc_rs::set_panic_policy(PanicPolicy::Collect);
+    let _ = cdrc_rs::take_reclamation_panics();
+
+    // The rest of a batch is processed even if some destructors panic.
+    drop_bombs::<C>(1000, dropped);
+    let panics = cdrc_rs::take_reclamation_panics();
+    assert_eq!(panics.len(), 100);
+    assert!(panics
+        .iter()
+        .all(|p| p.downcast_ref::<&str>() == Some(&"boom")));
+
+    // The thread can still reclaim afterwards.
+    drop_bombs::<C>(1000, dropped);
+    assert!(std::panic::catch_unwind(cdrc_rs::resume_reclamation_panic).is_err());
+    assert!(cdrc_rs::take_reclamation_panics().is_empty());
+}
+
+#[test]
+fn panicking_destructors_ebr() {
+    static DROPPED: AtomicUsize = AtomicUsize::new(0);
+    panicking_destructors::<cdrc_rs::CsEBR>(&DROPPED);
+}
+
+#[test]
+fn panicking_destructors_hp() {
+    static DROPPED: AtomicUsize = AtomicUsize::new(0);
+    panicking_destructors::<cdrc_rs::CsHP>(&DROPPED);
+}
@@ -0,0 +1,59 @@
+//! The panic policy is a process-wide setting, so this test runs in its own binary.
+
+use std::any::Any;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use cdrc_rs::{Cs, CsEBR, PanicPolicy, Rc};
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+/// An object whose destructor panics with the given message.
+struct Bomb(&'static str);
+
+impl Drop for Bomb {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::Relaxed);
+        panic!("{}", self.0);
+    }
+}
+
+fn message(payload: &(dyn Any + Send)) -> &str {
+    payload.downcast_ref::<String>().unwrap()
+}
+
+/// Drops an object for each of `messages`, and reclaims them.
+fn explode(messages: &[&'static str]) {
+    let target = DROPPED.load(Ordering::Relaxed) + messages.len();
+    for &msg in messages {
+        drop(Rc::<_, CsEBR>::new(Bomb(msg)));
+    }
+
+    let mut cs = CsEBR::new();
+    for _ in 0..1024 {
+        if DROPPED.load(Ordering::Relaxed) == target {
+            break;
+        }
+        cs.eager_reclaim();
+    }
+    assert_eq!(DROPPED.load(Ordering::Relaxed), target);
+}
+
+#[test]
+fn policies() {
+    cdrc_rs::set_panic_policy(PanicPolicy::LogAndContinue);
+    explode(&["logged"]);
+    assert!(cdrc_rs::take_reclamation_panics().is_empty());
+
+    cdrc_rs::set_panic_policy(PanicPolicy::Collect);
+    explode(&["first", "second"]);
+    let panics = cdrc_rs::take_reclamation_panics();
+    let mut messages = panics.iter().map(|p| message(&**p)).collect::<Vec<_>>();
+    messages.sort_unstable();
+    assert_eq!(messages, ["first", "second"]);
+
+    explode(&["rethrown"]);
+    let payload = std::panic::catch_unwind(cdrc_rs::resume_reclamation_panic).unwrap_err();
+    assert_eq!(message(&*payload), "rethrown");
+    assert!(cdrc_rs::take_reclamation_panics().is_empty());
+    cdrc_rs::set_panic_policy(PanicPolicy::Abort);
+}
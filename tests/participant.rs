@@ -0,0 +1,75 @@
+use cdrc_rs::{ebr_impl, hp_impl, CsEBR, CsHP, Rc, Snapshot};
+
+use std::panic::{self, AssertUnwindSafe};
+use std::thread;
+
+#[test]
+fn smoke_ebr() {
+    use ebr_impl::Participant;
+
+    thread::spawn(|| {
+        let collector = ebr_impl::default_collector();
+        let before = collector.num_participants();
+        assert!(!Participant::is_registered());
+
+        let participant = Participant::register();
+        assert!(Participant::is_registered());
+        assert_eq!(collector.num_participants(), before + 1);
+
+        // A pinned thread leaves once it is unpinned.
+        let cs = CsEBR::from(&participant);
+        let rc = Rc::<usize, CsEBR>::new(1);
+        let mut snapshot = Snapshot::new();
+        snapshot.protect(&rc, &cs);
+        participant.unregister();
+        assert!(!Participant::is_registered());
+        assert_eq!(collector.num_participants(), before + 1);
+        drop(snapshot);
+        drop(cs);
+        assert_eq!(collector.num_participants(), before);
+
+        // The thread is registered again on its next use.
+        drop(rc);
+        assert!(Participant::is_registered());
+        assert_eq!(collector.num_participants(), before + 1);
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn smoke_hp() {
+    use hp_impl::Participant;
+
+    thread::spawn(|| {
+        let domain = &hp_impl::DEFAULT_DOMAIN;
+        let before = domain.num_threads();
+        assert!(!Participant::is_registered());
+
+        let participant = Participant::register();
+        assert!(Participant::is_registered());
+        assert_eq!(domain.num_threads(), before + 1);
+
+        // A thread can not leave while it holds hazard pointers.
+        let cs = CsHP::from(&participant);
+        let rc = Rc::<usize, CsHP>::new(1);
+        let mut snapshot = Snapshot::new();
+        snapshot.protect(&rc, &cs);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| participant.unregister()));
+        assert!(result.is_err());
+        assert!(Participant::is_registered());
+
+        drop(snapshot);
+        drop(cs);
+        Participant::register().unregister();
+        assert!(!Participant::is_registered());
+        assert_eq!(domain.num_threads(), before);
+
+        // The thread is registered again on its next use.
+        drop(rc);
+        assert!(Participant::is_registered());
+        assert_eq!(domain.num_threads(), before + 1);
+    })
+    .join()
+    .unwrap();
+}
@@ -0,0 +1,39 @@
+//! The object pool is a process-wide setting, so these tests run in their own binary.
+
+use atomic::Atomic;
+use cdrc_rs::{Acquired, Counted, Cs, CsEBR, CsHP, TaggedCnt};
+
+#[test]
+fn reuse_ebr() {
+    cdrc_rs::set_object_pool_enabled(true);
+    let first = CsEBR::create_object::<Counted<_>>([1u64; 5]);
+    let _ = unsafe { CsEBR::own_object(first) };
+    let second = CsEBR::create_object::<Counted<_>>([2u64; 5]);
+    assert_eq!(first, second);
+    let _ = unsafe { CsEBR::own_object(second) };
+}
+
+#[test]
+fn no_reuse_while_protected_hp() {
+    cdrc_rs::set_object_pool_enabled(true);
+    let mut cs = CsHP::new();
+    let ptr = CsHP::create_object::<Counted<_>>([1u64; 7]);
+    let link = Atomic::new(TaggedCnt::new(ptr));
+    let mut shield = <CsHP as Cs>::RawShield::null();
+    assert!(cs.protect_snapshot(&link, &mut shield));
+
+    let _ = unsafe { CsHP::own_object(ptr) };
+    cs.eager_reclaim();
+    let other = CsHP::create_object::<Counted<_>>([2u64; 7]);
+    assert_ne!(ptr, other);
+
+    shield.clear();
+    cs.eager_reclaim();
+    let reused = CsHP::create_object::<Counted<_>>([3u64; 7]);
+    assert_eq!(ptr, reused);
+
+    unsafe {
+        let _ = CsHP::own_object(other);
+        let _ = CsHP::own_object(reused);
+    }
+}
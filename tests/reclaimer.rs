@@ -0,0 +1,88 @@
+use cdrc_rs::ebr_impl::Collector;
+use cdrc_rs::hp_impl::{Domain, Thread};
+use cdrc_rs::Reclaimer;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread::{self, ThreadId};
+use std::time::{Duration, Instant};
+
+const COUNT: usize = 10_000;
+
+/// Counts the objects dropped, and the ones dropped by the thread which retired them.
+#[derive(Default)]
+struct Drops {
+    total: AtomicUsize,
+    inline: AtomicUsize,
+}
+
+struct Tracked {
+    owner: ThreadId,
+    drops: Arc<Drops>,
+}
+
+impl Tracked {
+    fn new(drops: &Arc<Drops>) -> Self {
+        Self {
+            owner: thread::current().id(),
+            drops: drops.clone(),
+        }
+    }
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        if thread::current().id() == self.owner {
+            self.drops.inline.fetch_add(1, Ordering::Relaxed);
+        }
+        self.drops.total.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Waits until all objects are dropped, and checks that none of them was dropped inline.
+fn wait(drops: &Drops) {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while drops.total.load(Ordering::Relaxed) < COUNT {
+        assert!(Instant::now() < deadline, "the reclaimer got stuck");
+        thread::sleep(Duration::from_millis(1));
+    }
+    assert_eq!(drops.inline.load(Ordering::Relaxed), 0);
+}
+
+#[test]
+fn smoke_ebr() {
+    let collector = Collector::new();
+    let reclaimer = Reclaimer::spawn(collector.clone());
+    let drops = Arc::new(Drops::default());
+
+    thread::scope(|s| {
+        s.spawn(|| {
+            let handle = collector.register();
+            for _ in 0..COUNT {
+                let tracked = Tracked::new(&drops);
+                handle.pin().defer(move || drop(tracked));
+            }
+        });
+    });
+    wait(&drops);
+    reclaimer.stop();
+}
+
+#[test]
+fn smoke_hp() {
+    static DOMAIN: Domain = Domain::new();
+
+    let reclaimer = Reclaimer::spawn(&DOMAIN);
+    let drops = Arc::new(Drops::default());
+
+    thread::scope(|s| {
+        s.spawn(|| {
+            let thread = Thread::new(&DOMAIN);
+            for _ in 0..COUNT {
+                unsafe { thread.retire(Box::into_raw(Box::new(Tracked::new(&drops)))) };
+            }
+        });
+    });
+    wait(&drops);
+    reclaimer.stop();
+}
@@ -0,0 +1,45 @@
+use atomic::Ordering;
+use cdrc_rs::{hp_impl, AtomicRc, Cs, CsHP, Rc, Snapshot};
+
+use std::thread;
+
+#[test]
+fn register_onto_grown_array_hp() {
+    // Every retirement reclaims the objects of the domain.
+    let config = hp_impl::Config::builder()
+        .collect_interval(1)
+        .build()
+        .unwrap();
+    hp_impl::DEFAULT_DOMAIN.set_config(config);
+
+    let root = AtomicRc::<usize, CsHP>::new(0);
+    thread::scope(|scope| {
+        scope
+            .spawn(|| {
+                // Grow the hazard array of the thread record, and leave a retired object in the
+                // domain at exit.
+                let cs = CsHP::new();
+                let snapshots: Vec<_> = (0..256)
+                    .map(|_| {
+                        let mut snapshot = Snapshot::new();
+                        snapshot.load(&root, &cs);
+                        snapshot
+                    })
+                    .collect();
+                root.store(Rc::null(), Ordering::Release, &cs);
+                drop(snapshots);
+            })
+            .join()
+            .unwrap();
+    });
+    assert!(hp_impl::DEFAULT_DOMAIN.num_garbages() > 0);
+
+    // The next thread registers onto the grown record, which must not reclaim the object while
+    // the thread is being registered.
+    thread::spawn(|| {
+        let _cs = CsHP::new();
+        assert!(hp_impl::Participant::is_registered());
+    })
+    .join()
+    .unwrap();
+}
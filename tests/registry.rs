@@ -0,0 +1,136 @@
+use cdrc_rs::ebr_impl::{Collector, Config, Registry};
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+
+const THREADS: usize = 8;
+const ROUNDS: usize = 16;
+const DEFERS: usize = 256;
+
+fn config(registry: Registry) -> Config {
+    Config::builder()
+        .registry(registry)
+        .stall_threshold(Duration::from_millis(10))
+        .build()
+        .unwrap()
+}
+
+/// Runs threads which join, defer functions, and leave repeatedly, so that the slots are reused.
+fn churn(collector: &Collector, executed: &Arc<AtomicUsize>) {
+    thread::scope(|s| {
+        for _ in 0..THREADS {
+            s.spawn(|| {
+                for _ in 0..ROUNDS {
+                    let handle = collector.register();
+                    for _ in 0..DEFERS {
+                        let executed = executed.clone();
+                        handle.pin().defer(move || {
+                            executed.fetch_add(1, Ordering::Relaxed);
+                        });
+                    }
+                }
+            });
+        }
+    });
+}
+
+/// Checks that all deferred functions are executed after participants have come and gone.
+fn check_reclaims(registry: Registry) {
+    let collector = Collector::with_config(config(registry));
+    let executed = Arc::new(AtomicUsize::new(0));
+    churn(&collector, &executed);
+
+    let handle = collector.register();
+    handle.synchronize();
+    while executed.load(Ordering::Relaxed) < THREADS * ROUNDS * DEFERS {
+        handle.pin().flush();
+    }
+    assert_eq!(collector.num_participants(), 1);
+}
+
+#[test]
+fn switch_registry() {
+    let collector = Collector::new();
+    let executed = Arc::new(AtomicUsize::new(0));
+    thread::scope(|s| {
+        s.spawn(|| churn(&collector, &executed));
+        for i in 0..64 {
+            let registry = if i % 2 == 0 {
+                Registry::Array
+            } else {
+                Registry::List
+            };
+            collector.set_config(
+                collector
+                    .config()
+                    .into_builder()
+                    .registry(registry)
+                    .build()
+                    .unwrap(),
+            );
+            thread::sleep(Duration::from_millis(1));
+        }
+    });
+
+    let handle = collector.register();
+    handle.synchronize();
+    while executed.load(Ordering::Relaxed) < THREADS * ROUNDS * DEFERS {
+        handle.pin().flush();
+    }
+}
+
+/// Checks that a participant pinned for long is reported, and keeps the epoch from advancing.
+fn check_stall(registry: Registry) {
+    let collector = Collector::with_config(config(registry));
+
+    // A thread which stays pinned until it is told to leave.
+    let (pinned_tx, pinned_rx) = mpsc::channel();
+    let (leave_tx, leave_rx) = mpsc::channel::<()>();
+    let stuck = {
+        let collector = collector.clone();
+        thread::Builder::new()
+            .name("stuck".into())
+            .spawn(move || {
+                let handle = collector.register();
+                let guard = handle.pin();
+                pinned_tx.send(guard.local_epoch()).unwrap();
+                leave_rx.recv().unwrap();
+            })
+            .unwrap()
+    };
+    let pinned = pinned_rx.recv().unwrap();
+
+    let handle = collector.register();
+    let stalled = loop {
+        handle.pin().flush();
+        let stalled = collector.stalled_participants();
+        if !stalled.is_empty() {
+            break stalled;
+        }
+        thread::sleep(Duration::from_millis(1));
+    };
+    assert_eq!(stalled[0].thread_id, stuck.thread().id());
+    // The stuck participant keeps the global epoch within one advancement of its epoch.
+    assert!(collector.global_epoch().wrapping_sub(pinned) <= 1);
+
+    leave_tx.send(()).unwrap();
+    stuck.join().unwrap();
+    let epoch = collector.global_epoch();
+    while collector.global_epoch().wrapping_sub(epoch) < 2 {
+        handle.pin().flush();
+    }
+}
+
+#[test]
+fn smoke_array() {
+    check_reclaims(Registry::Array);
+    check_stall(Registry::Array);
+}
+
+#[test]
+fn smoke_list() {
+    check_reclaims(Registry::List);
+    check_stall(Registry::List);
+}
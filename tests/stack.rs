@@ -0,0 +1,146 @@
+use atomic::Ordering;
+use cdrc_rs::{
+    AtomicRcNoWeak, CompareExchangeErrorRc, Cs, Pointer, RcNoWeak, SnapshotNoWeak, StrongPtr,
+};
+
+use std::mem::ManuallyDrop;
+
+struct Node<T, C: Cs> {
+    next: AtomicRcNoWeak<Self, C>,
+    value: ManuallyDrop<T>,
+}
+
+/// Treiber's stack. Its nodes are never weakly referenced, so they don't carry a weak count.
+struct Stack<T, C: Cs> {
+    head: AtomicRcNoWeak<Node<T, C>, C>,
+}
+
+impl<T, C: Cs> Stack<T, C> {
+    pub fn new() -> Self {
+        Self {
+            head: AtomicRcNoWeak::null(),
+        }
+    }
+
+    pub fn pop(&self, cs: &C) -> Option<T> {
+        let mut head = SnapshotNoWeak::new();
+        let mut next = SnapshotNoWeak::new();
+
+        loop {
+            head.load(&self.head, cs);
+            let head_node = head.as_ref()?;
+            next.load(&head_node.next, cs);
+
+            if self
+                .head
+                .compare_exchange(
+                    head.as_ptr(),
+                    &next,
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                    cs,
+                )
+                .is_ok()
+            {
+                // Only the thread which unlinked the node takes its value.
+                let value = unsafe { core::ptr::read(&*head_node.value) };
+                return Some(value);
+            }
+        }
+    }
+}
+
+impl<T: Send + Sync, C: Cs> Stack<T, C> {
+    pub fn push(&self, value: T, cs: &C) {
+        let mut node = RcNoWeak::new(Node {
+            next: AtomicRcNoWeak::null(),
+            value: ManuallyDrop::new(value),
+        });
+        let mut head = SnapshotNoWeak::new();
+
+        loop {
+            head.load(&self.head, cs);
+            unsafe { node.deref() }.next.swap(
+                RcNoWeak::from_snapshot(&head, cs),
+                Ordering::Relaxed,
+                cs,
+            );
+
+            match self.head.compare_exchange(
+                head.as_ptr(),
+                node,
+                Ordering::Release,
+                Ordering::Relaxed,
+                cs,
+            ) {
+                Ok(_) => return,
+                Err(CompareExchangeErrorRc { desired, .. }) => node = desired,
+            }
+        }
+    }
+}
+
+impl<T, C: Cs> Drop for Stack<T, C> {
+    fn drop(&mut self) {
+        let cs = &C::new();
+        while self.pop(cs).is_some() {}
+    }
+}
+
+fn smoke<C: Cs>() {
+    use crossbeam_utils::thread;
+
+    const THREADS: usize = 30;
+    const ELEMENTS_PER_THREADS: usize = 1000;
+
+    let stack = &Stack::new();
+
+    thread::scope(|s| {
+        for t in 0..THREADS {
+            s.spawn(move |_| {
+                let cs = &mut C::new();
+                for i in 0..ELEMENTS_PER_THREADS {
+                    stack.push((t * ELEMENTS_PER_THREADS + i).to_string(), cs);
+                    cs.clear();
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    let popped = thread::scope(|s| {
+        let handles = (0..THREADS)
+            .map(|_| {
+                s.spawn(move |_| {
+                    let cs = &mut C::new();
+                    let mut popped = Vec::new();
+                    for _ in 0..ELEMENTS_PER_THREADS {
+                        popped.push(stack.pop(cs).unwrap().parse::<usize>().unwrap());
+                        cs.clear();
+                    }
+                    popped
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect::<Vec<_>>()
+    })
+    .unwrap();
+
+    assert!(stack.pop(&C::new()).is_none());
+    let mut popped = popped;
+    popped.sort_unstable();
+    assert!(popped.into_iter().eq(0..THREADS * ELEMENTS_PER_THREADS));
+}
+
+#[test]
+fn smoke_ebr() {
+    smoke::<cdrc_rs::CsEBR>();
+}
+
+#[test]
+fn smoke_hp() {
+    smoke::<cdrc_rs::CsHP>();
+}
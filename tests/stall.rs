@@ -0,0 +1,65 @@
+use cdrc_rs::ebr_impl::{Collector, Config};
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn detect_stalled_participant() {
+    let config = Config::builder()
+        .stall_threshold(Duration::from_millis(10))
+        .build()
+        .unwrap();
+    let collector = Collector::with_config(config);
+    let reported = Arc::new(AtomicUsize::new(0));
+    {
+        let reported = reported.clone();
+        collector.set_stall_handler(move |participant| {
+            assert_eq!(participant.thread_name.as_deref(), Some("stuck"));
+            reported.fetch_add(1, Ordering::Relaxed);
+        });
+    }
+
+    // A thread which stays pinned until it is told to leave.
+    let (pinned_tx, pinned_rx) = mpsc::channel();
+    let (leave_tx, leave_rx) = mpsc::channel::<()>();
+    let stuck = {
+        let collector = collector.clone();
+        thread::Builder::new()
+            .name("stuck".into())
+            .spawn(move || {
+                let handle = collector.register();
+                let _guard = handle.pin();
+                pinned_tx.send(()).unwrap();
+                leave_rx.recv().unwrap();
+            })
+            .unwrap()
+    };
+    pinned_rx.recv().unwrap();
+
+    let handle = collector.register();
+    let stalled = loop {
+        handle.pin().flush();
+        let stalled = collector.stalled_participants();
+        if !stalled.is_empty() {
+            break stalled;
+        }
+        thread::sleep(Duration::from_millis(1));
+    };
+    assert_eq!(stalled.len(), 1);
+    assert_eq!(stalled[0].thread_name.as_deref(), Some("stuck"));
+    assert_eq!(stalled[0].thread_id, stuck.thread().id());
+    assert!(stalled[0].duration >= Duration::from_millis(10));
+
+    // The stall is reported once.
+    for _ in 0..16 {
+        handle.pin().flush();
+    }
+    assert_eq!(reported.load(Ordering::Relaxed), 1);
+
+    leave_tx.send(()).unwrap();
+    stuck.join().unwrap();
+    handle.pin().flush();
+    assert!(collector.stalled_participants().is_empty());
+}
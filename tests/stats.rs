@@ -0,0 +1,85 @@
+use cdrc_rs::{Cs, LatencyHistogram, Rc, Stats};
+
+use std::thread;
+
+/// Allocates and drops `count` objects in a new thread, reclaims them, and returns the
+/// statistics of that thread.
+fn churn<C: Cs>(count: usize, thread_stats: fn() -> Stats) -> Stats {
+    thread::spawn(move || {
+        // Register the thread first, as the events of an unregistered thread are recorded in the
+        // backend itself.
+        drop(C::new());
+        for i in 0..count {
+            drop(Rc::<_, C>::new([i; 4]));
+        }
+        let mut cs = C::new();
+        for _ in 0..16 {
+            cs.eager_reclaim();
+        }
+        thread_stats()
+    })
+    .join()
+    .unwrap()
+}
+
+fn check<C: Cs>(
+    stats: fn() -> Stats,
+    thread_stats: fn() -> Stats,
+    latency_histogram: fn() -> LatencyHistogram,
+) -> (Stats, Stats) {
+    const COUNT: usize = 10_000;
+
+    let before = stats();
+    let latency_before = latency_histogram();
+    let thread = churn::<C>(COUNT, thread_stats);
+    let after = stats();
+    let latency_after = latency_histogram();
+
+    assert!(latency_after.count() > latency_before.count());
+
+    assert_eq!(thread.allocated, COUNT as u64);
+    assert_eq!(thread.retired, COUNT as u64);
+    assert!(after.allocated - before.allocated >= COUNT as u64);
+    assert!(after.retired - before.retired >= COUNT as u64);
+    assert!(after.disposed - before.disposed >= COUNT as u64);
+    assert!(after.destroyed - before.destroyed >= COUNT as u64);
+    (thread, after)
+}
+
+#[test]
+fn smoke_ebr() {
+    let (thread, after) = check::<cdrc_rs::CsEBR>(
+        cdrc_rs::stats_ebr,
+        cdrc_rs::thread_stats_ebr,
+        cdrc_rs::latency_histogram_ebr,
+    );
+    assert!(thread.epoch_advances > 0);
+    assert!(after.epoch_advances >= thread.epoch_advances);
+    assert_eq!(after.scans, 0);
+}
+
+#[test]
+fn smoke_hp() {
+    let (thread, after) = check::<cdrc_rs::CsHP>(
+        cdrc_rs::stats_hp,
+        cdrc_rs::thread_stats_hp,
+        cdrc_rs::latency_histogram_hp,
+    );
+    assert!(thread.scans > 0);
+    assert!(after.scans >= thread.scans);
+    assert_eq!(after.epoch_advances, 0);
+}
+
+#[test]
+fn read_without_registering() {
+    use cdrc_rs::{ebr_impl, hp_impl};
+
+    thread::spawn(|| {
+        assert_eq!(cdrc_rs::thread_stats_ebr(), Stats::default());
+        assert_eq!(cdrc_rs::thread_stats_hp(), Stats::default());
+        assert!(!ebr_impl::Participant::is_registered());
+        assert!(!hp_impl::Participant::is_registered());
+    })
+    .join()
+    .unwrap();
+}